@@ -41,6 +41,7 @@ async fn main() {
             adapter: JsonAdapter::new(DATA_PATH, false),
             sync_policy: SyncPolicy::Periodic(Duration::from_secs(5)),
         },
+        ..Default::default()
     };
     let db = Db::open_with_config(config).unwrap();
 
@@ -71,8 +72,18 @@ async fn main() {
 
 // Handler for the index page
 async fn index(State(db): State<Db>) -> impl IntoResponse {
-    let pending_todos = db.get_all_by(|t: &Todo| !t.completed).unwrap();
-    let completed_todos = db.get_all_by(|t: &Todo| t.completed).unwrap();
+    let pending_todos = db
+        .query::<Todo>()
+        .filter(|t| !t.completed)
+        .order_by(|t| t.name.to_lowercase())
+        .fetch()
+        .unwrap();
+    let completed_todos = db
+        .query::<Todo>()
+        .filter(|t| t.completed)
+        .order_by(|t| t.name.to_lowercase())
+        .fetch()
+        .unwrap();
 
     Html(render_page(&pending_todos, &completed_todos).into_string())
 }
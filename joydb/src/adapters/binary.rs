@@ -0,0 +1,180 @@
+//! Adapters of compact binary formats (MessagePack and CBOR), which differ only in the encoding.
+
+use crate::JoydbError;
+use serde::{Serialize, de::DeserializeOwned};
+use std::path::Path;
+
+use super::fs_utils;
+
+/// Encoding of a binary format.
+pub(super) trait Codec {
+    /// Extension of the files written by the partitioned adapter.
+    const EXTENSION: &'static str;
+
+    fn encode<T: Serialize>(data: &T) -> Result<Vec<u8>, JoydbError>;
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, JoydbError>;
+}
+
+pub(super) fn write_to_file<C: Codec, T: Serialize>(
+    data: &T,
+    file_path: &Path,
+) -> Result<(), JoydbError> {
+    let bytes = C::encode(data)?;
+
+    fs_utils::safe_write(file_path, &bytes)?;
+
+    Ok(())
+}
+
+pub(super) fn read_from_file<C: Codec, T: DeserializeOwned>(
+    file_path: &Path,
+) -> Result<T, JoydbError> {
+    let bytes = fs_utils::read_file_bytes(file_path)?;
+    C::decode(&bytes)
+}
+
+/// Generates a unified and a partitioned adapter of a binary format encoded with the given [Codec].
+macro_rules! binary_adapters {
+    (
+        codec: $codec:ty,
+        format: $format:literal,
+        unified: $unified:ident,
+        partitioned: $partitioned:ident $(,)?
+    ) => {
+        #[doc = concat!("A ", $format, " adapter.")]
+        #[doc = concat!("Stores the entire state in a single ", $format, " file.")]
+        ///
+        #[doc = concat!($format, " is a compact binary format, which is faster to parse than JSON.")]
+        pub struct $unified {
+            #[doc = concat!("Path to the ", $format, " file where the state is stored.")]
+            file_path: ::std::path::PathBuf,
+        }
+
+        impl $crate::adapters::FromPath for $unified {
+            fn from_path<P: AsRef<::std::path::Path>>(file_path: P) -> Self {
+                Self::new(file_path)
+            }
+        }
+
+        impl $unified {
+            pub fn new<P: AsRef<::std::path::Path>>(file_path: P) -> Self {
+                Self {
+                    file_path: file_path.as_ref().to_path_buf(),
+                }
+            }
+        }
+
+        impl $crate::adapters::UnifiedAdapter for $unified {
+            fn write_state<S: $crate::State>(&self, state: &S) -> Result<(), $crate::JoydbError> {
+                $crate::adapters::binary::write_to_file::<$codec, _>(state, &self.file_path)
+            }
+
+            fn load_state<S: $crate::State>(&self) -> Result<S, $crate::JoydbError> {
+                if self.file_path.exists() {
+                    if !self.file_path.is_file() {
+                        // If the path exists but is not a file, then return an error
+                        Err($crate::JoydbError::NotFile(self.file_path.clone()))
+                    } else {
+                        // Otherwise read the state from the existing file
+                        $crate::adapters::binary::read_from_file::<$codec, S>(&self.file_path)
+                    }
+                } else {
+                    // If the file does not exist, create a new file with empty state
+                    let empty_state = S::default();
+                    $crate::adapters::UnifiedAdapter::write_state(self, &empty_state)?;
+                    Ok(empty_state)
+                }
+            }
+        }
+
+        impl $crate::adapters::Adapter for $unified {
+            type Target = $crate::adapters::Unified<Self>;
+
+            fn path(&self) -> Option<&::std::path::Path> {
+                Some(&self.file_path)
+            }
+        }
+
+        #[doc = concat!("A ", $format, " partitioned adapter.")]
+        #[doc = concat!("Stores every relation in a separate ", $format, " file.")]
+        pub struct $partitioned {
+            #[doc = concat!("Path to the directory where the partitioned ", $format, " files are stored.")]
+            dir_path: ::std::path::PathBuf,
+        }
+
+        impl $crate::adapters::FromPath for $partitioned {
+            fn from_path<P: AsRef<::std::path::Path>>(dir_path: P) -> Self {
+                Self::new(dir_path)
+            }
+        }
+
+        impl $partitioned {
+            pub fn new<P: AsRef<::std::path::Path>>(dir_path: P) -> Self {
+                Self {
+                    dir_path: dir_path.as_ref().to_path_buf(),
+                }
+            }
+
+            /// Build the file path for the relation of a given model.
+            fn relation_file_path<M: $crate::Model>(&self) -> ::std::path::PathBuf {
+                let extension = <$codec as $crate::adapters::binary::Codec>::EXTENSION;
+                self.dir_path
+                    .join(format!("{}.{extension}", M::model_name()))
+            }
+        }
+
+        impl $crate::adapters::PartitionedAdapter for $partitioned {
+            fn write_relation<M: $crate::Model>(
+                &self,
+                relation: &$crate::Relation<M>,
+            ) -> Result<(), $crate::JoydbError> {
+                $crate::adapters::binary::write_to_file::<$codec, _>(
+                    relation,
+                    &self.relation_file_path::<M>(),
+                )
+            }
+
+            fn load_relation<M: $crate::Model>(
+                &self,
+            ) -> Result<$crate::Relation<M>, $crate::JoydbError> {
+                let file_path = self.relation_file_path::<M>();
+                if file_path.exists() {
+                    if !file_path.is_file() {
+                        // If the path exists but is not a file, then return an error
+                        Err($crate::JoydbError::NotFile(file_path))
+                    } else {
+                        $crate::adapters::binary::read_from_file::<$codec, _>(&file_path)
+                    }
+                } else {
+                    // If the file does not exist, the relation is empty. The file is created on the
+                    // first write, so that loading never writes anything (e.g. in read-only mode).
+                    Ok($crate::Relation::<M>::default())
+                }
+            }
+
+            fn load_state<S: $crate::State>(&self) -> Result<S, $crate::JoydbError> {
+                if self.dir_path.exists() {
+                    if !self.dir_path.is_dir() {
+                        return Err($crate::JoydbError::NotDirectory(self.dir_path.clone()));
+                    }
+                } else {
+                    // Create a directory if it does not exist
+                    ::std::fs::create_dir_all(&self.dir_path)?;
+                }
+
+                S::load_with_partitioned_adapter(self)
+            }
+        }
+
+        impl $crate::adapters::Adapter for $partitioned {
+            type Target = $crate::adapters::Partitioned<Self>;
+
+            fn path(&self) -> Option<&::std::path::Path> {
+                Some(&self.dir_path)
+            }
+        }
+    };
+}
+
+pub(super) use binary_adapters;
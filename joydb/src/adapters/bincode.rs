@@ -0,0 +1,117 @@
+use crate::adapters::{Adapter, FromPath, Unified, UnifiedAdapter};
+use crate::{JoydbError, state::State};
+use std::path::{Path, PathBuf};
+
+use super::fs_utils;
+
+/// Magic string at the beginning of every file written by [BincodeAdapter].
+const MAGIC: &[u8; 8] = b"JOYDBBIN";
+
+/// Version of the file layout. Must be bumped whenever the encoding of the state changes
+/// in a backward incompatible way (e.g. bincode configuration).
+const FORMAT_VERSION: u16 = 1;
+
+/// Length of the header: magic string followed by the format version (little-endian).
+const HEADER_LEN: usize = MAGIC.len() + size_of::<u16>();
+
+/// A bincode adapter.
+/// Stores the entire state in a single binary file, which is the fastest to write and to load.
+///
+/// The encoded state is preceded by a header with a magic string and a format version,
+/// so files that were not written by this adapter (or by an incompatible version of it)
+/// are refused with [JoydbError::Deserialize].
+///
+/// Bincode is not a self-describing format, so it's only suitable for data that are read by the
+/// same program which wrote them, e.g. caches. In particular:
+/// - any change of the models makes the existing files unreadable;
+/// - [migrations](crate::migration) are not supported;
+/// - models must not use serde attributes that rely on a self-describing format
+///   (e.g. `#[serde(flatten)]`, `#[serde(untagged)]` or `#[serde(skip_serializing_if)]`).
+pub struct BincodeAdapter {
+    /// Path to the file where the state is stored.
+    file_path: PathBuf,
+}
+
+impl FromPath for BincodeAdapter {
+    fn from_path<P: AsRef<Path>>(file_path: P) -> Self {
+        Self::new(file_path)
+    }
+}
+
+impl BincodeAdapter {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl UnifiedAdapter for BincodeAdapter {
+    fn write_state<S: State>(&self, state: &S) -> Result<(), JoydbError> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::serde::encode_into_std_write(state, &mut bytes, bincode::config::standard())
+            .map_err(|e| JoydbError::Serialize(Box::new(e)))?;
+
+        fs_utils::safe_write(&self.file_path, &bytes)?;
+
+        Ok(())
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        if self.file_path.exists() {
+            if !self.file_path.is_file() {
+                // If the path exists but is not a file, then return an error
+                Err(JoydbError::NotFile(self.file_path.clone()))
+            } else {
+                // Otherwise read the state from the existing file
+                let bytes = fs_utils::read_file_bytes(&self.file_path)?;
+                decode(&bytes).map_err(|message| {
+                    let message = format!("{}: {message}", self.file_path.display());
+                    JoydbError::Deserialize(message.into())
+                })
+            }
+        } else {
+            // If the file does not exist, create a new file with empty state
+            let empty_state = S::default();
+            UnifiedAdapter::write_state(self, &empty_state)?;
+            Ok(empty_state)
+        }
+    }
+}
+
+impl Adapter for BincodeAdapter {
+    type Target = Unified<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
+}
+
+/// Checks the header and decodes the state that follows it.
+fn decode<S: State>(bytes: &[u8]) -> Result<S, String> {
+    if bytes.len() < HEADER_LEN || !bytes.starts_with(MAGIC) {
+        return Err("not a Joydb bincode file".to_string());
+    }
+
+    let (header, body) = bytes.split_at(HEADER_LEN);
+    let version = u16::from_le_bytes([header[MAGIC.len()], header[MAGIC.len() + 1]]);
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported bincode format version {version}, expected {FORMAT_VERSION}"
+        ));
+    }
+
+    let (state, read_len) = bincode::serde::decode_from_slice(body, bincode::config::standard())
+        .map_err(|e| e.to_string())?;
+    if read_len != body.len() {
+        // The state is decoded, but it does not occupy the entire file, which means that the file
+        // was written with different models.
+        return Err(format!(
+            "{} unexpected trailing bytes",
+            body.len() - read_len
+        ));
+    }
+    Ok(state)
+}
@@ -0,0 +1,27 @@
+use crate::JoydbError;
+
+use super::binary::{Codec, binary_adapters};
+
+/// CBOR encoding, see [ciborium].
+struct Cbor;
+
+impl Codec for Cbor {
+    const EXTENSION: &'static str = "cbor";
+
+    fn encode<T: ::serde::Serialize>(data: &T) -> Result<Vec<u8>, JoydbError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(data, &mut bytes).map_err(|e| JoydbError::Serialize(Box::new(e)))?;
+        Ok(bytes)
+    }
+
+    fn decode<T: ::serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, JoydbError> {
+        ciborium::from_reader(bytes).map_err(|e| JoydbError::Deserialize(Box::new(e)))
+    }
+}
+
+binary_adapters! {
+    codec: Cbor,
+    format: "CBOR",
+    unified: CborAdapter,
+    partitioned: CborPartitionedAdapter,
+}
@@ -48,10 +48,9 @@ impl PartitionedAdapter for CsvAdapter {
                 load_relation_from_csv_file::<M>(&file_path)
             }
         } else {
-            // If the file does not exist, create a new file with empty relation
-            let empty_relation = Relation::<M>::default();
-            self.write_relation(&empty_relation)?;
-            Ok(empty_relation)
+            // If the file does not exist, the relation is empty. The file is created on the
+            // first write, so that loading never writes anything (e.g. in read-only mode).
+            Ok(Relation::<M>::default())
         }
     }
 
@@ -71,6 +70,10 @@ impl PartitionedAdapter for CsvAdapter {
 
 impl Adapter for CsvAdapter {
     type Target = Partitioned<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.dir_path)
+    }
 }
 
 fn load_relation_from_csv_file<M: Model>(file_path: &PathBuf) -> Result<Relation<M>, JoydbError> {
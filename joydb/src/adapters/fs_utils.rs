@@ -31,3 +31,13 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
     file.read_to_string(&mut contents)?;
     Ok(contents)
 }
+
+/// Reads the content of a file and returns it as bytes.
+// Some of the adapters which are behind feature gate may not use this function.
+#[allow(dead_code)]
+pub fn read_file_bytes<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path.as_ref())?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    Ok(contents)
+}
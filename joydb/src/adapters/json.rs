@@ -57,6 +57,10 @@ impl UnifiedAdapter for JsonAdapter {
 
 impl Adapter for JsonAdapter {
     type Target = Unified<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
 }
 
 /// A JSON partitioned adapter.
@@ -104,10 +108,9 @@ impl PartitionedAdapter for JsonPartitionedAdapter {
                 read_from_file::<Relation<M>>(&file_path)
             }
         } else {
-            // If the file does not exist, create a new file with empty relation
-            let empty_relation = Relation::<M>::default();
-            self.write_relation(&empty_relation)?;
-            Ok(empty_relation)
+            // If the file does not exist, the relation is empty. The file is created on the
+            // first write, so that loading never writes anything (e.g. in read-only mode).
+            Ok(Relation::<M>::default())
         }
     }
 
@@ -127,9 +130,13 @@ impl PartitionedAdapter for JsonPartitionedAdapter {
 
 impl Adapter for JsonPartitionedAdapter {
     type Target = Partitioned<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.dir_path)
+    }
 }
 
-fn write_to_file<T: ::serde::Serialize>(
+pub(super) fn write_to_file<T: ::serde::Serialize>(
     data: &T,
     file_path: &PathBuf,
     pretty: bool,
@@ -146,7 +153,9 @@ fn write_to_file<T: ::serde::Serialize>(
     Ok(())
 }
 
-fn read_from_file<T: ::serde::de::DeserializeOwned>(file_path: &PathBuf) -> Result<T, JoydbError> {
+pub(super) fn read_from_file<T: ::serde::de::DeserializeOwned>(
+    file_path: &PathBuf,
+) -> Result<T, JoydbError> {
     let content = fs_utils::read_file(file_path)?;
     let data = serde_json::from_str(&content).map_err(|e| JoydbError::Deserialize(Box::new(e)))?;
     Ok(data)
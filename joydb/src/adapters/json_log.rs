@@ -0,0 +1,282 @@
+use crate::adapters::{Adapter, FromPath, Unified, UnifiedAdapter};
+use crate::{Change, JoydbError, Model, Relation, RelationVisitor, state::State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::fs_utils;
+use super::json::{read_from_file, write_to_file};
+
+/// Size of the log file (in bytes), after which the log is compacted into the snapshot by default.
+const DEFAULT_COMPACTION_THRESHOLD: u64 = 1024 * 1024;
+
+/// An append-only log adapter.
+///
+/// Instead of rewriting the entire state on every flush, it appends every insert, update and
+/// delete as a single JSON line to the log file. So the cost of a write is proportional to the
+/// size of the change, not to the size of the database.
+///
+/// The state is stored in 2 files:
+/// - the snapshot (e.g. `data.json`), which has the same format as [JsonAdapter](super::JsonAdapter);
+/// - the log (e.g. `data.json.log`), which contains changes made after the snapshot was written.
+///
+/// On open the log is replayed on top of the snapshot. Once the log grows over the
+/// compaction threshold, the entire state is written to the snapshot and the log is truncated.
+pub struct JsonLogAdapter {
+    /// Path to the snapshot file.
+    file_path: PathBuf,
+
+    /// Path to the log file.
+    log_path: PathBuf,
+
+    /// Size of the log file (in bytes), after which the log is compacted into the snapshot.
+    compaction_threshold: u64,
+
+    log: Mutex<LogState>,
+}
+
+#[derive(Default)]
+struct LogState {
+    /// Lines with the changes received since the last write.
+    pending: String,
+
+    /// Current size of the log file in bytes.
+    size: u64,
+
+    /// Is set when the log file may end with an incomplete line (e.g. after a crash or a failed
+    /// write), so nothing can be appended to it anymore.
+    needs_compaction: bool,
+}
+
+impl FromPath for JsonLogAdapter {
+    fn from_path<P: AsRef<Path>>(file_path: P) -> Self {
+        Self::new(file_path)
+    }
+}
+
+impl JsonLogAdapter {
+    /// Creates a new adapter with the given path of the snapshot file.
+    /// The log file is placed next to it with `.log` suffix appended.
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        let file_path = file_path.as_ref().to_path_buf();
+        let mut log_path = file_path.clone().into_os_string();
+        log_path.push(".log");
+        Self {
+            file_path,
+            log_path: PathBuf::from(log_path),
+            compaction_threshold: DEFAULT_COMPACTION_THRESHOLD,
+            log: Mutex::new(LogState::default()),
+        }
+    }
+
+    /// Sets the size of the log file in bytes, after which the log is compacted into the snapshot.
+    /// By default it's 1 MiB.
+    pub fn with_compaction_threshold(mut self, threshold: u64) -> Self {
+        self.compaction_threshold = threshold;
+        self
+    }
+
+    /// Writes the entire state to the snapshot and truncates the log.
+    fn compact<S: State>(&self, state: &S, log: &mut LogState) -> Result<(), JoydbError> {
+        write_to_file(state, &self.file_path, false)?;
+        // If it fails right here, the log is replayed on top of the snapshot which already
+        // contains the changes. It's harmless, because replaying a change twice has the same
+        // effect as replaying it once.
+        fs_utils::safe_write(&self.log_path, b"")?;
+
+        log.pending.clear();
+        log.size = 0;
+        log.needs_compaction = false;
+        Ok(())
+    }
+
+    /// Appends the pending changes to the log.
+    fn append(&self, log: &mut LogState) -> Result<(), JoydbError> {
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)
+            .and_then(|mut file| {
+                file.write_all(log.pending.as_bytes())?;
+                file.sync_data()
+            });
+        if let Err(err) = result {
+            // A part of the pending changes might be written
+            log.needs_compaction = true;
+            return Err(err.into());
+        }
+
+        log.size += log.pending.len() as u64;
+        log.pending.clear();
+        Ok(())
+    }
+}
+
+impl UnifiedAdapter for JsonLogAdapter {
+    fn write_state<S: State>(&self, state: &S) -> Result<(), JoydbError> {
+        let mut log = self.log.lock().unwrap();
+        // If there are no pending changes, the state was changed without recording the changes,
+        // so the entire state must be written.
+        let is_compaction_needed = log.needs_compaction
+            || log.pending.is_empty()
+            || log.size + log.pending.len() as u64 > self.compaction_threshold;
+
+        if is_compaction_needed {
+            self.compact(state, &mut log)
+        } else {
+            self.append(&mut log)
+        }
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        // The state may be reloaded, so changes received before must be discarded
+        *self.log.lock().unwrap() = LogState::default();
+
+        let mut state = if self.file_path.exists() {
+            if !self.file_path.is_file() {
+                return Err(JoydbError::NotFile(self.file_path.clone()));
+            }
+            read_from_file::<S>(&self.file_path)?
+        } else {
+            // If the file does not exist, create a new file with empty state
+            let empty_state = S::default();
+            write_to_file(&empty_state, &self.file_path, false)?;
+            empty_state
+        };
+
+        if self.log_path.exists() {
+            let content = fs_utils::read_file(&self.log_path)?;
+            let mut log = self.log.lock().unwrap();
+            log.size = content.len() as u64;
+
+            // A line without the trailing newline was not written completely, so it's ignored.
+            let complete_content = match content.rfind('\n') {
+                Some(position) => &content[..=position],
+                None => "",
+            };
+            if complete_content.len() != content.len() {
+                log.needs_compaction = true;
+            }
+
+            let mut replay = Replay::parse(complete_content)?;
+            state.visit_relations_mut(&mut replay)?;
+            state.reset_dirty();
+        }
+
+        Ok(state)
+    }
+}
+
+impl Adapter for JsonLogAdapter {
+    type Target = Unified<Self>;
+
+    const RECORDS_CHANGES: bool = true;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
+
+    fn data_paths(&self) -> Vec<&Path> {
+        vec![&self.file_path, &self.log_path]
+    }
+
+    fn discard_changes(&self) {
+        let mut log = self.log.lock().unwrap();
+        log.pending.clear();
+        // The discarded changes may include ones, which were not rolled back
+        // (e.g. received before a failed write), so the entire state must be written next time.
+        log.needs_compaction = true;
+    }
+
+    fn record_change<M: Model>(&self, change: &Change<M>) -> Result<(), JoydbError> {
+        let mut log = self.log.lock().unwrap();
+        match change {
+            Change::Inserted(record) => push_entry(&mut log.pending, Operation::Insert, record),
+            Change::Updated { old, new } if old.id() != new.id() => {
+                push_entry(&mut log.pending, Operation::Delete, old)?;
+                push_entry(&mut log.pending, Operation::Insert, new)
+            }
+            Change::Updated { new, .. } => push_entry(&mut log.pending, Operation::Update, new),
+            Change::Deleted(record) => push_entry(&mut log.pending, Operation::Delete, record),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A line of the log, e.g. `{"op":"insert","model":"User","record":{"id":1,"name":"Alice"}}`.
+/// Deleted records are stored entirely, because ids are not required to be serializable.
+#[derive(Serialize)]
+struct Entry<'a, M> {
+    op: Operation,
+    model: &'static str,
+    record: &'a M,
+}
+
+#[derive(Deserialize)]
+struct OwnedEntry {
+    op: Operation,
+    model: String,
+    record: serde_json::Value,
+}
+
+fn push_entry<M: Model>(pending: &mut String, op: Operation, record: &M) -> Result<(), JoydbError> {
+    let entry = Entry {
+        op,
+        model: M::model_name(),
+        record,
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| JoydbError::Serialize(Box::new(e)))?;
+    pending.push_str(&line);
+    pending.push('\n');
+    Ok(())
+}
+
+/// Applies the changes from the log to the relations.
+struct Replay {
+    /// Changes grouped by model name in the order they were made.
+    entries: HashMap<String, Vec<(Operation, serde_json::Value)>>,
+}
+
+impl Replay {
+    fn parse(content: &str) -> Result<Self, JoydbError> {
+        let mut entries: HashMap<String, Vec<_>> = HashMap::new();
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            let entry: OwnedEntry =
+                serde_json::from_str(line).map_err(|e| JoydbError::Deserialize(Box::new(e)))?;
+            entries
+                .entry(entry.model)
+                .or_default()
+                .push((entry.op, entry.record));
+        }
+        Ok(Self { entries })
+    }
+}
+
+impl RelationVisitor for Replay {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        let Some(entries) = self.entries.remove(M::model_name()) else {
+            return Ok(());
+        };
+        for (op, value) in entries {
+            let record: M =
+                serde_json::from_value(value).map_err(|e| JoydbError::Deserialize(Box::new(e)))?;
+            match op {
+                Operation::Insert | Operation::Update => relation.put(record),
+                Operation::Delete => {
+                    relation.purge(&record.id())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
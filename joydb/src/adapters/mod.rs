@@ -7,18 +7,32 @@
 //! - _Unified_ - uses a single file to store the state. It writes and reads the entire state at once. Usually requires a file path.
 //! - _Partitioned_ - uses multiple files to store the state. It writes and reads each relation separately. Usually requires directory path.
 //!
+//! Besides that, an adapter may receive changes of individual records (see [Adapter::record_change])
+//! to persist only the changes instead of the entire state, like [JsonLogAdapter] does.
+//!
 //! ## Supported adapters
 //!
 //! The following adapters are implemented out of the box and can be used with the corresponding
 //! feature flag enabled.
 //!
-//! | Adapter                  | Format | Type        | Feature flag |
-//! | ------------------------ | ------ | ----------- | ------------ |
-//! | [JsonAdapter]            | JSON   | Unified     | `json`       |
-//! | [JsonPartitionedAdapter] | JSON   | Partitioned | `json`       |
-//! | [RonAdapter]             | RON    | Unified     | `ron`        |
-//! | [RonPartitionedAdapter]  | RON    | Partitioned | `ron`        |
-//! | [CsvAdapter]             | CSV    | Paritioned  | `csv`        |
+//! | Adapter                         | Format      | Type            | Feature flag |
+//! | ------------------------------- | ----------- | --------------- | ------------ |
+//! | [JsonAdapter]                   | JSON        | Unified         | `json`       |
+//! | [JsonPartitionedAdapter]        | JSON        | Partitioned     | `json`       |
+//! | [JsonLogAdapter]                | JSON        | Append-only log | `json`       |
+//! | [RonAdapter]                    | RON         | Unified         | `ron`        |
+//! | [RonPartitionedAdapter]         | RON         | Partitioned     | `ron`        |
+//! | [YamlAdapter]                   | YAML        | Unified         | `yaml`       |
+//! | [YamlPartitionedAdapter]        | YAML        | Partitioned     | `yaml`       |
+//! | [TomlAdapter]                   | TOML        | Unified         | `toml`       |
+//! | [TomlPartitionedAdapter]        | TOML        | Partitioned     | `toml`       |
+//! | [MessagePackAdapter]            | MessagePack | Unified         | `msgpack`    |
+//! | [MessagePackPartitionedAdapter] | MessagePack | Partitioned     | `msgpack`    |
+//! | [CborAdapter]                   | CBOR        | Unified         | `cbor`       |
+//! | [CborPartitionedAdapter]        | CBOR        | Partitioned     | `cbor`       |
+//! | [BincodeAdapter]                | Bincode     | Unified         | `bincode`    |
+//! | [SqliteAdapter]                 | SQLite      | Partitioned     | `sqlite`     |
+//! | [CsvAdapter]                    | CSV         | Paritioned      | `csv`        |
 //!
 //!
 
@@ -34,13 +48,58 @@ mod json;
 #[cfg(feature = "json")]
 pub use json::{JsonAdapter, JsonPartitionedAdapter};
 
+#[cfg(feature = "json")]
+mod json_log;
+
+#[cfg(feature = "json")]
+pub use json_log::JsonLogAdapter;
+
 #[cfg(feature = "ron")]
 mod ron;
 
 #[cfg(feature = "ron")]
 pub use ron::{RonAdapter, RonPartitionedAdapter};
 
-use crate::{JoydbError, state::State};
+#[cfg(feature = "yaml")]
+mod yaml;
+
+#[cfg(feature = "yaml")]
+pub use yaml::{YamlAdapter, YamlPartitionedAdapter};
+
+#[cfg(feature = "toml")]
+mod toml;
+
+#[cfg(feature = "toml")]
+pub use toml::{TomlAdapter, TomlPartitionedAdapter};
+
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+mod binary;
+
+#[cfg(feature = "msgpack")]
+mod msgpack;
+
+#[cfg(feature = "msgpack")]
+pub use msgpack::{MessagePackAdapter, MessagePackPartitionedAdapter};
+
+#[cfg(feature = "cbor")]
+mod cbor;
+
+#[cfg(feature = "cbor")]
+pub use cbor::{CborAdapter, CborPartitionedAdapter};
+
+#[cfg(feature = "bincode")]
+mod bincode;
+
+#[cfg(feature = "bincode")]
+pub use bincode::BincodeAdapter;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteAdapter;
+
+use crate::{Change, Changeset, JoydbError, state::State};
 use crate::{Model, Relation};
 use std::marker::PhantomData;
 use std::path::Path;
@@ -64,6 +123,51 @@ pub trait Adapter: Send + 'static {
     fn load_state<S: State>(&self) -> Result<S, JoydbError> {
         Self::Target::load_state(self)
     }
+
+    /// Load the state in [read-only mode](crate::JoydbMode::ReadOnly), which is called only
+    /// if the data exist. The adapter must not write anything.
+    ///
+    /// By default it's the same as [load_state](Self::load_state). Adapters which keep
+    /// a connection open (e.g. [SqliteAdapter]) override it to open the connection read-only.
+    fn load_state_read_only<S: State>(&self) -> Result<S, JoydbError> {
+        self.load_state()
+    }
+
+    /// Path to the file or directory where the data are stored.
+    ///
+    /// When a database is opened, a lockfile is placed next to it (e.g. `data.json.lock`)
+    /// to prevent other processes from opening the same data.
+    /// Returns `None` by default, which means no locking.
+    fn path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Paths of all the files and directories written by the adapter, which are checked
+    /// for changes made by other programs (see [JoydbConfig](crate::JoydbConfig)).
+    /// By default it's only [path](Self::path).
+    fn data_paths(&self) -> Vec<&Path> {
+        self.path().into_iter().collect()
+    }
+
+    /// Whether the adapter needs to receive changes of individual records
+    /// with [record_change](Self::record_change).
+    /// Tracking the changes has a cost, so it's disabled by default.
+    const RECORDS_CHANGES: bool = false;
+
+    /// Receives a change of a single record, if [RECORDS_CHANGES](Self::RECORDS_CHANGES) is `true`.
+    ///
+    /// It's called right after every insert, update or delete (or on commit of a transaction),
+    /// regardless of the sync policy. The changes received since the last
+    /// [write_state](Self::write_state) are meant to be persisted on the next call of it.
+    fn record_change<M: Model>(&self, change: &Change<M>) -> Result<(), JoydbError> {
+        let _ = change;
+        Ok(())
+    }
+
+    /// Discards the changes received with [record_change](Self::record_change) since the last
+    /// [write_state](Self::write_state), because they were rolled back (e.g. a transaction
+    /// failed to flush on commit).
+    fn discard_changes(&self) {}
 }
 
 /// A tiny helper trait that allows to implement [Adapter] trait in terms of
@@ -100,6 +204,11 @@ impl<PA: PartitionedAdapter> BlanketAdapter for Partitioned<PA> {
     }
 
     fn load_state<S: State>(target: &PA) -> Result<S, JoydbError> {
+        // Relations are deserialized separately, so there is no place to store the schema version
+        if S::SCHEMA_VERSION > 0 {
+            let message = "schema migrations are not supported by partitioned adapters";
+            return Err(JoydbError::Deserialize(message.into()));
+        }
         target.load_state()
     }
 }
@@ -113,6 +222,9 @@ pub trait UnifiedAdapter {
 
     /// Is called only once when the database is opened or created.
     /// Usually the adapter should check if the files exist and if not, create them.
+    ///
+    /// In [read-only mode](crate::JoydbMode::ReadOnly) it's called only if the data exist,
+    /// so the adapter must not write anything in that case.
     fn load_state<S: State>(&self) -> Result<S, JoydbError>;
 }
 
@@ -126,10 +238,27 @@ pub trait PartitionedAdapter {
     /// Write a relation to a file system or any other storage.
     fn write_relation<M: Model>(&self, relation: &Relation<M>) -> Result<(), JoydbError>;
 
+    /// Write changes of a relation made since the last flush.
+    /// It's called for every dirty relation instead of [write_relation](Self::write_relation).
+    ///
+    /// By default it rewrites the entire relation. Adapters that can apply changes
+    /// incrementally (e.g. update a single row in a table) should override it and
+    /// look up the changed records with [Relation::record].
+    fn write_relation_changes<M: Model>(
+        &self,
+        relation: &Relation<M>,
+        changeset: &Changeset<M::Id>,
+    ) -> Result<(), JoydbError> {
+        let _ = changeset;
+        self.write_relation(relation)
+    }
+
     /// Load the entire state (all relations) using the given partitioned adapter.
+    /// See [UnifiedAdapter::load_state].
     fn load_state<S: State>(&self) -> Result<S, JoydbError>;
 
     /// Load a relation from a file system or any other storage.
+    /// It must not write anything, a missing relation is loaded as an empty one.
     ///
     /// It's meant to be called by implementation of [crate::State], because State knows concrete type of M.
     fn load_relation<M: Model>(&self) -> Result<Relation<M>, JoydbError>;
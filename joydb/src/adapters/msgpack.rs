@@ -0,0 +1,27 @@
+use crate::JoydbError;
+
+use super::binary::{Codec, binary_adapters};
+
+/// MessagePack encoding, see [rmp_serde].
+struct MessagePack;
+
+impl Codec for MessagePack {
+    const EXTENSION: &'static str = "msgpack";
+
+    fn encode<T: ::serde::Serialize>(data: &T) -> Result<Vec<u8>, JoydbError> {
+        // Structs are encoded as maps (not arrays), so the data stay self-describing
+        // and fields can be added or reordered later.
+        rmp_serde::to_vec_named(data).map_err(|e| JoydbError::Serialize(Box::new(e)))
+    }
+
+    fn decode<T: ::serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, JoydbError> {
+        rmp_serde::from_slice(bytes).map_err(|e| JoydbError::Deserialize(Box::new(e)))
+    }
+}
+
+binary_adapters! {
+    codec: MessagePack,
+    format: "MessagePack",
+    unified: MessagePackAdapter,
+    partitioned: MessagePackPartitionedAdapter,
+}
@@ -68,6 +68,10 @@ impl UnifiedAdapter for RonAdapter {
 
 impl Adapter for RonAdapter {
     type Target = Unified<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
 }
 
 /// A RON adapter.
@@ -123,10 +127,9 @@ impl PartitionedAdapter for RonPartitionedAdapter {
                 read_from_file(&file_path)
             }
         } else {
-            // If the file does not exist, create a new file with empty relation
-            let empty_relation = Relation::<M>::default();
-            self.write_relation(&empty_relation)?;
-            Ok(empty_relation)
+            // If the file does not exist, the relation is empty. The file is created on the
+            // first write, so that loading never writes anything (e.g. in read-only mode).
+            Ok(Relation::<M>::default())
         }
     }
 
@@ -146,6 +149,10 @@ impl PartitionedAdapter for RonPartitionedAdapter {
 
 impl Adapter for RonPartitionedAdapter {
     type Target = Partitioned<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.dir_path)
+    }
 }
 
 fn write_to_file<T: ::serde::Serialize>(
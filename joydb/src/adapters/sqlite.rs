@@ -0,0 +1,286 @@
+use crate::adapters::{Adapter, FromPath, Partitioned, PartitionedAdapter};
+use crate::{Changeset, JoydbError, state::State};
+use crate::{Model, Relation};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A SQLite adapter.
+/// Stores every relation in a separate table of a single SQLite database file.
+///
+/// A table is named after the model and has 2 columns: `id` (the primary key) and `record`,
+/// both serialized to JSON. So the data can be inspected with `sqlite3` or any other SQLite client:
+///
+/// ```sql
+/// SELECT json_extract(record, '$.name') FROM User;
+/// ```
+///
+/// Every flush is written within a single SQLite transaction and only the records that were
+/// changed since the previous flush are written.
+///
+/// The ids are serialized with [Model::serialize_id], which a manual implementation of [Model]
+/// has to override.
+pub struct SqliteAdapter {
+    /// Path to the SQLite database file.
+    file_path: PathBuf,
+
+    /// Connection to the database. It's opened when the state is loaded, read-only in
+    /// [read-only mode](crate::JoydbMode::ReadOnly).
+    connection: Mutex<Option<Connection>>,
+}
+
+impl FromPath for SqliteAdapter {
+    fn from_path<P: AsRef<Path>>(file_path: P) -> Self {
+        Self::new(file_path)
+    }
+}
+
+impl SqliteAdapter {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Calls the closure with the connection, opening it first if needed.
+    fn with_connection<T, F>(&self, f: F) -> Result<T, JoydbError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, JoydbError>,
+    {
+        // The database file is created if it does not exist
+        self.with_connection_opened_with(OpenFlags::default(), f)
+    }
+
+    /// Calls the closure with the connection, opening it with the given flags first if needed.
+    fn with_connection_opened_with<T, F>(&self, flags: OpenFlags, f: F) -> Result<T, JoydbError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, JoydbError>,
+    {
+        let mut connection = self.connection.lock().unwrap();
+        if connection.is_none() {
+            if self.file_path.exists() && !self.file_path.is_file() {
+                return Err(JoydbError::NotFile(self.file_path.clone()));
+            }
+            let opened =
+                Connection::open_with_flags(&self.file_path, flags).map_err(sqlite_error)?;
+            *connection = Some(opened);
+        }
+        f(connection.as_mut().unwrap())
+    }
+
+    /// Calls the closure within a transaction, which is committed if the closure succeeds
+    /// and rolled back otherwise.
+    fn in_transaction<F>(&self, f: F) -> Result<(), JoydbError>
+    where
+        F: FnOnce(&Tables) -> Result<(), JoydbError>,
+    {
+        self.with_connection(|connection| {
+            let transaction = connection.transaction().map_err(sqlite_error)?;
+            f(&Tables {
+                connection: &transaction,
+            })?;
+            transaction.commit().map_err(sqlite_error)
+        })
+    }
+}
+
+impl PartitionedAdapter for SqliteAdapter {
+    fn write_relation<M: Model>(&self, relation: &Relation<M>) -> Result<(), JoydbError> {
+        self.in_transaction(|tables| tables.write_relation(relation))
+    }
+
+    fn write_relation_changes<M: Model>(
+        &self,
+        relation: &Relation<M>,
+        changeset: &Changeset<M::Id>,
+    ) -> Result<(), JoydbError> {
+        self.in_transaction(|tables| tables.write_relation_changes(relation, changeset))
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        self.with_connection(|connection| S::load_with_partitioned_adapter(&Tables { connection }))
+    }
+
+    fn load_relation<M: Model>(&self) -> Result<Relation<M>, JoydbError> {
+        self.with_connection(|connection| Tables { connection }.load_relation())
+    }
+}
+
+impl Adapter for SqliteAdapter {
+    type Target = Partitioned<Self>;
+
+    // Overridden to write all the dirty relations within one transaction.
+    fn write_state<S: State>(&self, state: &S) -> Result<(), JoydbError> {
+        self.in_transaction(|tables| S::write_with_partitioned_adapter(state, tables))
+    }
+
+    fn load_state_read_only<S: State>(&self) -> Result<S, JoydbError> {
+        self.with_connection_opened_with(OpenFlags::SQLITE_OPEN_READ_ONLY, |connection| {
+            S::load_with_partitioned_adapter(&Tables { connection })
+        })
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
+}
+
+/// Reads and writes the tables using the given connection (or transaction).
+///
+/// It implements [PartitionedAdapter], so it can be passed to
+/// [State::write_with_partitioned_adapter] and [State::load_with_partitioned_adapter].
+/// It does not manage transactions, it's up to [SqliteAdapter].
+struct Tables<'a> {
+    connection: &'a Connection,
+}
+
+impl Tables<'_> {
+    fn create_table<M: Model>(&self) -> Result<(), JoydbError> {
+        let sql = format!(
+            r#"CREATE TABLE IF NOT EXISTS "{}" (id TEXT PRIMARY KEY NOT NULL, record TEXT NOT NULL)"#,
+            M::model_name()
+        );
+        self.connection.execute(&sql, ()).map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn table_exists<M: Model>(&self) -> Result<bool, JoydbError> {
+        let sql = "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1";
+        let row = self
+            .connection
+            .query_row(sql, [M::model_name()], |_| Ok(()))
+            .optional()
+            .map_err(sqlite_error)?;
+        Ok(row.is_some())
+    }
+
+    /// Inserts the record, or replaces the existing one with the same id.
+    fn upsert_record<M: Model>(&self, record: &M) -> Result<(), JoydbError> {
+        // Unlike `INSERT OR REPLACE`, the upsert keeps the rowid, which preserves the order
+        let sql = format!(
+            r#"INSERT INTO "{}" (id, record) VALUES (?1, ?2)
+               ON CONFLICT (id) DO UPDATE SET record = excluded.record"#,
+            M::model_name()
+        );
+        let id = serialize(&SerializeId::<M>::new(&record.id()))?;
+        let record = serialize(record)?;
+        self.connection
+            .prepare_cached(&sql)
+            .and_then(|mut statement| statement.execute((id, record)))
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn delete_record<M: Model>(&self, id: &M::Id) -> Result<(), JoydbError> {
+        let sql = format!(r#"DELETE FROM "{}" WHERE id = ?1"#, M::model_name());
+        let id = serialize(&SerializeId::<M>::new(id))?;
+        self.connection
+            .prepare_cached(&sql)
+            .and_then(|mut statement| statement.execute([id]))
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+}
+
+impl PartitionedAdapter for Tables<'_> {
+    fn write_relation<M: Model>(&self, relation: &Relation<M>) -> Result<(), JoydbError> {
+        self.create_table::<M>()?;
+
+        let sql = format!(r#"DELETE FROM "{}""#, M::model_name());
+        self.connection.execute(&sql, ()).map_err(sqlite_error)?;
+
+        for record in relation.records() {
+            self.upsert_record(record)?;
+        }
+        Ok(())
+    }
+
+    fn write_relation_changes<M: Model>(
+        &self,
+        relation: &Relation<M>,
+        changeset: &Changeset<M::Id>,
+    ) -> Result<(), JoydbError> {
+        self.create_table::<M>()?;
+
+        for id in changeset.deleted() {
+            self.delete_record::<M>(id)?;
+        }
+        for record in changeset
+            .updated()
+            .iter()
+            .filter_map(|id| relation.record(id))
+        {
+            self.upsert_record(record)?;
+        }
+
+        // New rows are inserted in the order of the relation, so they are loaded in the same order
+        let mut positions: Vec<usize> = changeset
+            .inserted()
+            .iter()
+            .filter_map(|id| relation.position(id))
+            .collect();
+        positions.sort_unstable();
+        for position in positions {
+            self.upsert_record(&relation.records()[position])?;
+        }
+        Ok(())
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        S::load_with_partitioned_adapter(self)
+    }
+
+    fn load_relation<M: Model>(&self) -> Result<Relation<M>, JoydbError> {
+        // A missing table is an empty relation. It's created on the first write,
+        // so that loading never writes anything (e.g. in read-only mode).
+        if !self.table_exists::<M>()? {
+            return Ok(Relation::default());
+        }
+
+        let sql = format!(r#"SELECT record FROM "{}" ORDER BY rowid"#, M::model_name());
+        let mut statement = self.connection.prepare(&sql).map_err(sqlite_error)?;
+        let rows = statement
+            .query_map((), |row| row.get::<_, String>(0))
+            .map_err(sqlite_error)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let json = row.map_err(sqlite_error)?;
+            let record =
+                serde_json::from_str(&json).map_err(|e| JoydbError::Deserialize(Box::new(e)))?;
+            records.push(record);
+        }
+        Ok(Relation::new_with_records(records))
+    }
+}
+
+/// Serializes an id with [Model::serialize_id].
+struct SerializeId<'a, M: Model> {
+    id: &'a M::Id,
+    model: PhantomData<M>,
+}
+
+impl<'a, M: Model> SerializeId<'a, M> {
+    fn new(id: &'a M::Id) -> Self {
+        Self {
+            id,
+            model: PhantomData,
+        }
+    }
+}
+
+impl<M: Model> ::serde::Serialize for SerializeId<'_, M> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        M::serialize_id(self.id, serializer)
+    }
+}
+
+fn serialize<T: ::serde::Serialize>(value: &T) -> Result<String, JoydbError> {
+    serde_json::to_string(value).map_err(|e| JoydbError::Serialize(Box::new(e)))
+}
+
+fn sqlite_error(err: rusqlite::Error) -> JoydbError {
+    JoydbError::Custom(Box::new(err))
+}
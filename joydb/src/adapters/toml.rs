@@ -0,0 +1,173 @@
+use crate::adapters::{
+    Adapter, FromPath, Partitioned, PartitionedAdapter, Unified, UnifiedAdapter,
+};
+use crate::{JoydbError, RelationInspector, state::State};
+use crate::{Model, Relation};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::fs_utils;
+
+/// A TOML adapter.
+/// Stores the entire state in a single TOML file, where every relation is an array of tables
+/// named after the model.
+///
+/// TOML cannot represent some values (e.g. `None` inside of arrays), so writing a state with
+/// such values fails with [JoydbError::Serialize].
+pub struct TomlAdapter {
+    /// Path to the TOML file where the state is stored.
+    file_path: PathBuf,
+}
+
+impl FromPath for TomlAdapter {
+    fn from_path<P: AsRef<Path>>(file_path: P) -> Self {
+        Self::new(file_path)
+    }
+}
+
+impl TomlAdapter {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl UnifiedAdapter for TomlAdapter {
+    fn write_state<S: State>(&self, state: &S) -> Result<(), JoydbError> {
+        let toml_string = toml::to_string(state).map_err(|err| {
+            // The error does not tell which relation failed, so find it out by serializing
+            // relations one by one.
+            match state.inspect_relations(&mut UnserializableRelationFinder) {
+                Err(relation_err) => relation_err,
+                Ok(()) => JoydbError::Serialize(Box::new(err)),
+            }
+        })?;
+
+        fs_utils::safe_write(&self.file_path, toml_string.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        if self.file_path.exists() {
+            if !self.file_path.is_file() {
+                // If the path exists but is not a file, then return an error
+                Err(JoydbError::NotFile(self.file_path.clone()))
+            } else {
+                // Otherwise read the state from the existing file
+                read_from_file::<S>(&self.file_path)
+            }
+        } else {
+            // If the file does not exist, create a new file with empty state
+            let empty_state = S::default();
+            UnifiedAdapter::write_state(self, &empty_state)?;
+            Ok(empty_state)
+        }
+    }
+}
+
+impl Adapter for TomlAdapter {
+    type Target = Unified<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
+}
+
+/// A TOML partitioned adapter.
+/// Stores every relation in a separate TOML file as an array of tables named after the model.
+pub struct TomlPartitionedAdapter {
+    /// Path to the directory where the partitioned TOML files are stored.
+    dir_path: PathBuf,
+}
+
+impl FromPath for TomlPartitionedAdapter {
+    fn from_path<P: AsRef<Path>>(dir_path: P) -> Self {
+        Self::new(dir_path)
+    }
+}
+
+impl TomlPartitionedAdapter {
+    pub fn new<P: AsRef<Path>>(dir_path: P) -> Self {
+        Self {
+            dir_path: dir_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Build the file path for the relation of a given model.
+    fn relation_file_path<M: Model>(&self) -> PathBuf {
+        self.dir_path.join(format!("{}.toml", M::model_name()))
+    }
+}
+
+impl PartitionedAdapter for TomlPartitionedAdapter {
+    fn write_relation<M: Model>(&self, relation: &Relation<M>) -> Result<(), JoydbError> {
+        let toml_string = relation_to_string(relation)?;
+        fs_utils::safe_write(self.relation_file_path::<M>(), toml_string.as_bytes())?;
+        Ok(())
+    }
+
+    fn load_relation<M: Model>(&self) -> Result<Relation<M>, JoydbError> {
+        let file_path = self.relation_file_path::<M>();
+        if file_path.exists() {
+            if !file_path.is_file() {
+                // If the path exists but is not a file, then return an error
+                Err(JoydbError::NotFile(file_path))
+            } else {
+                // A TOML document must be a table, so the relation is stored under the model name
+                let mut tables: BTreeMap<String, Relation<M>> = read_from_file(&file_path)?;
+                Ok(tables.remove(M::model_name()).unwrap_or_default())
+            }
+        } else {
+            // If the file does not exist, the relation is empty. The file is created on the
+            // first write, so that loading never writes anything (e.g. in read-only mode).
+            Ok(Relation::<M>::default())
+        }
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        if self.dir_path.exists() {
+            if !self.dir_path.is_dir() {
+                return Err(JoydbError::NotDirectory(self.dir_path.clone()));
+            }
+        } else {
+            // Create a directory if it does not exist
+            std::fs::create_dir_all(&self.dir_path)?;
+        }
+
+        S::load_with_partitioned_adapter(self)
+    }
+}
+
+impl Adapter for TomlPartitionedAdapter {
+    type Target = Partitioned<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.dir_path)
+    }
+}
+
+/// Serializes a relation as a TOML document with a single array of tables named after the model.
+fn relation_to_string<M: Model>(relation: &Relation<M>) -> Result<String, JoydbError> {
+    let document = BTreeMap::from([(M::model_name(), relation)]);
+    toml::to_string(&document).map_err(|err| {
+        let message = format!("Failed to serialize {} as TOML: {err}", M::model_name());
+        JoydbError::Serialize(message.into())
+    })
+}
+
+/// Looks for the first relation that cannot be represented in TOML and returns its error.
+struct UnserializableRelationFinder;
+
+impl RelationInspector for UnserializableRelationFinder {
+    fn inspect<M: Model>(&mut self, relation: &Relation<M>) -> Result<(), JoydbError> {
+        relation_to_string(relation).map(|_| ())
+    }
+}
+
+fn read_from_file<T: ::serde::de::DeserializeOwned>(file_path: &PathBuf) -> Result<T, JoydbError> {
+    let content = fs_utils::read_file(file_path)?;
+    let data = toml::from_str(&content).map_err(|e| JoydbError::Deserialize(Box::new(e)))?;
+    Ok(data)
+}
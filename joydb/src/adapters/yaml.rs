@@ -0,0 +1,147 @@
+use crate::adapters::{
+    Adapter, FromPath, Partitioned, PartitionedAdapter, Unified, UnifiedAdapter,
+};
+use crate::{JoydbError, state::State};
+use crate::{Model, Relation};
+use std::path::{Path, PathBuf};
+
+use super::fs_utils;
+
+/// A YAML adapter.
+/// Stores the entire state in a single YAML file.
+///
+/// YAML is easy to read and to edit by hand, which makes it a good fit for seed data.
+pub struct YamlAdapter {
+    /// Path to the YAML file where the state is stored.
+    file_path: PathBuf,
+}
+
+impl FromPath for YamlAdapter {
+    fn from_path<P: AsRef<Path>>(file_path: P) -> Self {
+        Self::new(file_path)
+    }
+}
+
+impl YamlAdapter {
+    pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl UnifiedAdapter for YamlAdapter {
+    fn write_state<S: State>(&self, state: &S) -> Result<(), JoydbError> {
+        write_to_file(state, &self.file_path)
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        if self.file_path.exists() {
+            if !self.file_path.is_file() {
+                // If the path exists but is not a file, then return an error
+                Err(JoydbError::NotFile(self.file_path.clone()))
+            } else {
+                // Otherwise read the state from the existing file
+                read_from_file::<S>(&self.file_path)
+            }
+        } else {
+            // If the file does not exist, create a new file with empty state
+            let empty_state = S::default();
+            UnifiedAdapter::write_state(self, &empty_state)?;
+            Ok(empty_state)
+        }
+    }
+}
+
+impl Adapter for YamlAdapter {
+    type Target = Unified<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.file_path)
+    }
+}
+
+/// A YAML partitioned adapter.
+/// Stores every relation in a separate YAML file.
+pub struct YamlPartitionedAdapter {
+    /// Path to the directory where the partitioned YAML files are stored.
+    dir_path: PathBuf,
+}
+
+impl FromPath for YamlPartitionedAdapter {
+    fn from_path<P: AsRef<Path>>(dir_path: P) -> Self {
+        Self::new(dir_path)
+    }
+}
+
+impl YamlPartitionedAdapter {
+    pub fn new<P: AsRef<Path>>(dir_path: P) -> Self {
+        Self {
+            dir_path: dir_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Build the file path for the relation of a given model.
+    fn relation_file_path<M: Model>(&self) -> PathBuf {
+        self.dir_path.join(format!("{}.yaml", M::model_name()))
+    }
+}
+
+impl PartitionedAdapter for YamlPartitionedAdapter {
+    fn write_relation<M: Model>(&self, relation: &Relation<M>) -> Result<(), JoydbError> {
+        write_to_file(relation, &self.relation_file_path::<M>())
+    }
+
+    fn load_relation<M: Model>(&self) -> Result<Relation<M>, JoydbError> {
+        let file_path = self.relation_file_path::<M>();
+        if file_path.exists() {
+            if !file_path.is_file() {
+                // If the path exists but is not a file, then return an error
+                Err(JoydbError::NotFile(file_path))
+            } else {
+                read_from_file::<Relation<M>>(&file_path)
+            }
+        } else {
+            // If the file does not exist, the relation is empty. The file is created on the
+            // first write, so that loading never writes anything (e.g. in read-only mode).
+            Ok(Relation::<M>::default())
+        }
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        if self.dir_path.exists() {
+            if !self.dir_path.is_dir() {
+                return Err(JoydbError::NotDirectory(self.dir_path.clone()));
+            }
+        } else {
+            // Create a directory if it does not exist
+            std::fs::create_dir_all(&self.dir_path)?;
+        }
+
+        S::load_with_partitioned_adapter(self)
+    }
+}
+
+impl Adapter for YamlPartitionedAdapter {
+    type Target = Partitioned<Self>;
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.dir_path)
+    }
+}
+
+fn write_to_file<T: ::serde::Serialize>(data: &T, file_path: &PathBuf) -> Result<(), JoydbError> {
+    let yaml_string =
+        serde_norway::to_string(data).map_err(|e| JoydbError::Serialize(Box::new(e)))?;
+
+    fs_utils::safe_write(file_path, yaml_string.as_bytes())?;
+
+    Ok(())
+}
+
+fn read_from_file<T: ::serde::de::DeserializeOwned>(file_path: &PathBuf) -> Result<T, JoydbError> {
+    let content = fs_utils::read_file(file_path)?;
+    let data =
+        serde_norway::from_str(&content).map_err(|e| JoydbError::Deserialize(Box::new(e)))?;
+    Ok(data)
+}
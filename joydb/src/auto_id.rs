@@ -0,0 +1,173 @@
+use crate::state::{GetRelation, State};
+use crate::{IndexDefinition, JoydbError, Model};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// A model which id is generated by the database on insert.
+/// See [`Joydb::insert_new`](crate::Joydb::insert_new).
+///
+/// With [`derive(Model)`](crate::Model) it's implemented by marking the id field with
+/// `#[joydb(id, auto = "...")]`, where the strategy is one of:
+///
+/// | Strategy    | Generator   | Id type                  | Feature flag |
+/// |-------------|-------------|--------------------------|--------------|
+/// | `increment` | [Increment] | Any primitive integer    |              |
+/// | `uuid_v4`   | `UuidV4`    | [`uuid::Uuid`][uuid]     | `uuid`       |
+/// | `uuid_v7`   | `UuidV7`    | [`uuid::Uuid`][uuid]     | `uuid`       |
+///
+/// [uuid]: https://docs.rs/uuid/latest/uuid/struct.Uuid.html
+pub trait AutoId: Model {
+    /// Strategy used to generate ids.
+    type Generator: IdGenerator<Self::Id>;
+
+    /// Assigns the generated id to the record.
+    fn set_id(&mut self, id: Self::Id);
+}
+
+/// A strategy to generate ids of type `Id`.
+pub trait IdGenerator<Id> {
+    /// Generates a new id, given the previously generated one.
+    /// Returns `None` if no more ids can be generated (e.g. on integer overflow).
+    fn generate(last_id: Option<&Id>) -> Option<Id>;
+
+    /// Restores the last generated id from the existing ids.
+    /// It's called when the relation has no generated ids yet (e.g. right after it's loaded).
+    ///
+    /// Returns `None` by default, which suits random ids.
+    fn restore_last_id(ids: impl Iterator<Item = Id>) -> Option<Id> {
+        let _ = ids;
+        None
+    }
+
+    /// Converts the last generated id to a number, which is persisted in [IdCounter].
+    ///
+    /// Returns `None` by default, which means nothing is persisted and the last id is
+    /// restored with [restore_last_id](Self::restore_last_id) after reopening.
+    fn to_counter(last_id: &Id) -> Option<u64> {
+        let _ = last_id;
+        None
+    }
+
+    /// Converts the persisted number back to the last generated id.
+    fn from_counter(counter: u64) -> Option<Id> {
+        let _ = counter;
+        None
+    }
+}
+
+/// The last id generated for a model.
+///
+/// Every state has a relation of counters (named `JoydbIdCounter`), which is persisted by
+/// the adapter the same way as the relations of the models. So the ids of deleted records
+/// are not reused after reopening. Only generators which implement
+/// [IdGenerator::to_counter] (e.g. [Increment]) keep their counters there.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdCounter {
+    /// Name of the model, see [Model::model_name].
+    pub model: String,
+
+    /// The last generated id, see [IdGenerator::to_counter].
+    pub last_id: u64,
+}
+
+impl Model for IdCounter {
+    type Id = String;
+
+    fn id(&self) -> Self::Id {
+        self.model.clone()
+    }
+
+    fn model_name() -> &'static str {
+        "JoydbIdCounter"
+    }
+
+    fn serialize_id<S: Serializer>(id: &Self::Id, serializer: S) -> Result<S::Ok, S::Error> {
+        id.serialize(serializer)
+    }
+
+    fn indexes() -> &'static [IndexDefinition<Self>] {
+        &[]
+    }
+}
+
+/// Inserts a new record with a generated id and updates the [IdCounter] of its relation.
+pub(crate) fn insert_new<S, M>(state: &mut S, record: &M) -> Result<M::Id, JoydbError>
+where
+    S: State + GetRelation<M>,
+    M: AutoId,
+{
+    let model = M::model_name().to_owned();
+    let counter = state
+        .id_counters()
+        .record(&model)
+        .map(|counter| counter.last_id);
+
+    let id = <S as GetRelation<M>>::get_relation_mut(state).insert_new(record, counter)?;
+
+    if let Some(last_id) = M::Generator::to_counter(&id) {
+        state
+            .id_counters_mut()
+            .upsert(&IdCounter { model, last_id })?;
+    }
+    Ok(id)
+}
+
+/// Generates sequential integer ids starting from `1`.
+///
+/// The counter is kept per relation and persisted in [IdCounter], so ids are never reused,
+/// even if the record with the greatest id is deleted.
+/// Ids which do not fit into `u64` are not persisted, instead the counter continues
+/// from the greatest existing id after reopening.
+pub struct Increment;
+
+macro_rules! impl_increment {
+    ($($int_type:ty),*) => {
+        $(
+            impl IdGenerator<$int_type> for Increment {
+                fn generate(last_id: Option<&$int_type>) -> Option<$int_type> {
+                    match last_id {
+                        Some(id) => id.checked_add(1),
+                        None => Some(1),
+                    }
+                }
+
+                fn restore_last_id(ids: impl Iterator<Item = $int_type>) -> Option<$int_type> {
+                    ids.max()
+                }
+
+                fn to_counter(last_id: &$int_type) -> Option<u64> {
+                    u64::try_from(*last_id).ok()
+                }
+
+                fn from_counter(counter: u64) -> Option<$int_type> {
+                    <$int_type>::try_from(counter).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_increment!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+/// Generates random UUIDs (version 4).
+#[cfg(feature = "uuid")]
+pub struct UuidV4;
+
+#[cfg(feature = "uuid")]
+impl IdGenerator<uuid::Uuid> for UuidV4 {
+    fn generate(_last_id: Option<&uuid::Uuid>) -> Option<uuid::Uuid> {
+        Some(uuid::Uuid::new_v4())
+    }
+}
+
+/// Generates time-ordered UUIDs (version 7).
+#[cfg(feature = "uuid")]
+pub struct UuidV7;
+
+#[cfg(feature = "uuid")]
+impl IdGenerator<uuid::Uuid> for UuidV7 {
+    fn generate(_last_id: Option<&uuid::Uuid>) -> Option<uuid::Uuid> {
+        Some(uuid::Uuid::now_v7())
+    }
+}
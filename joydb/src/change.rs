@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A change of a single record made by an insert, update or delete operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<M> {
+    /// A new record was inserted.
+    Inserted(M),
+
+    /// An existing record was updated.
+    Updated {
+        /// The record before the update.
+        old: M,
+        /// The record after the update.
+        new: M,
+    },
+
+    /// A record was deleted.
+    Deleted(M),
+}
+
+/// Ids of the records of a relation, that were inserted, updated or deleted since the last flush.
+///
+/// The changes are accumulated, so every id is present in at most one of the sets.
+/// E.g. a record that was inserted and then updated is reported only as inserted,
+/// and a record that was inserted and then deleted is not reported at all.
+#[derive(Debug, Clone)]
+pub struct Changeset<Id> {
+    inserted: HashSet<Id>,
+    updated: HashSet<Id>,
+    deleted: HashSet<Id>,
+}
+
+impl<Id: Eq + Hash> Changeset<Id> {
+    /// Ids of the records that did not exist at the last flush.
+    pub fn inserted(&self) -> &HashSet<Id> {
+        &self.inserted
+    }
+
+    /// Ids of the records that existed at the last flush and were updated since then.
+    pub fn updated(&self) -> &HashSet<Id> {
+        &self.updated
+    }
+
+    /// Ids of the records that existed at the last flush and were deleted since then.
+    pub fn deleted(&self) -> &HashSet<Id> {
+        &self.deleted
+    }
+
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, id: Id) {
+        // A record that is deleted and inserted again, is replaced
+        if self.deleted.remove(&id) {
+            self.updated.insert(id);
+        } else {
+            self.inserted.insert(id);
+        }
+    }
+
+    pub(crate) fn update(&mut self, id: Id) {
+        if !self.inserted.contains(&id) {
+            self.updated.insert(id);
+        }
+    }
+
+    pub(crate) fn delete(&mut self, id: Id) {
+        if !self.inserted.remove(&id) {
+            self.updated.remove(&id);
+            self.deleted.insert(id);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.inserted.clear();
+        self.updated.clear();
+        self.deleted.clear();
+    }
+}
+
+// Implement `Default` manually, otherwise derive would require `Id: Default`.
+impl<Id> Default for Changeset<Id> {
+    fn default() -> Self {
+        Self {
+            inserted: HashSet::new(),
+            updated: HashSet::new(),
+            deleted: HashSet::new(),
+        }
+    }
+}
@@ -1,12 +1,21 @@
 use crate::Model;
 use crate::adapters::{Adapter, FromPath};
+use crate::auto_id;
+use crate::fingerprint::Fingerprint;
+use crate::lock::FileLock;
+use crate::query::Query;
+use crate::subscription::Subscribers;
+use crate::transaction::Transaction;
 use crate::{
-    JoydbError, Relation,
-    state::{GetRelation, State},
+    AutoId, Change, Clock, Index, JoydbError, Relation, SystemClock,
+    state::{GetRelation, RelationVisitor, State},
 };
 use std::fmt::Debug;
+use std::hash::Hash;
+use std::io;
 use std::ops::Drop;
 use std::path::Path;
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -18,12 +27,14 @@ use std::time::Duration;
 ///
 /// # CRUD operations
 ///
-/// | Operation | Methods                                                                                                  |
-/// |-----------|----------------------------------------------------------------------------------------------------------|
-/// | Create    | [`insert`](Self::insert), [`upsert`](Self::upsert)                                                       |
-/// | Read      | [`get`](Self::get), [`get_all`](Self::get_all), [`get_all_by`](Self::get_all_by), [`count`](Self::count) |
-/// | Update    | [`update`](Self::update), [`upsert`](Self::upsert)                                                       |
-/// | Delete    | [`delete`](Self::delete), [`delete_all_by`](Self::delete_all_by)                                         |
+/// | Operation | Methods                                                                                                                                                                                                                                                                                                                            |
+/// |-----------|------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
+/// | Create    | [`insert`](Self::insert), [`insert_new`](Self::insert_new), [`upsert`](Self::upsert)                                                                                                                                                                                                                                               |
+/// | Read      | [`get`](Self::get), [`get_with_deleted`](Self::get_with_deleted), [`get_all`](Self::get_all), [`get_all_by`](Self::get_all_by), [`get_all_by_index`](Self::get_all_by_index), [`count`](Self::count), [`count_by`](Self::count_by), [`exists_by`](Self::exists_by), [`with_relation`](Self::with_relation), [`query`](Self::query) |
+/// | Update    | [`update`](Self::update), [`update_with`](Self::update_with), [`update_all_by`](Self::update_all_by), [`upsert`](Self::upsert)                                                                                                                                                                                                     |
+/// | Delete    | [`delete`](Self::delete), [`delete_all_by`](Self::delete_all_by), [`purge`](Self::purge), [`restore`](Self::restore)                                                                                                                                                                                                               |
+///
+/// Multiple operations can be executed atomically with [`transaction`](Self::transaction).
 ///
 #[derive(Debug)]
 pub struct Joydb<S: State, A: Adapter> {
@@ -43,14 +54,27 @@ impl<S: State, A: Adapter> Clone for Joydb<S, A> {
 impl<S: State, A: Adapter + FromPath> Joydb<S, A> {
     /// Opens a database from the given file or directory.
     /// If the database does not exist, it will be created.
+    ///
+    /// # Errors
+    /// Returns [JoydbError::Locked] if the database is already opened by another process.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, JoydbError> {
         let adapter = A::from_path(path);
-        let config = JoydbConfig {
-            mode: JoydbMode::Persistent {
-                adapter,
-                sync_policy: SyncPolicy::Instant,
-            },
-        };
+        let config = JoydbConfig::new(JoydbMode::Persistent {
+            adapter,
+            sync_policy: SyncPolicy::Instant,
+        });
+        Self::open_with_config(config)
+    }
+
+    /// Opens an existing database from the given file or directory in
+    /// [read-only mode](JoydbMode::ReadOnly).
+    ///
+    /// # Errors
+    /// Returns an error if the database does not exist or is opened for writing by
+    /// another process.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self, JoydbError> {
+        let adapter = A::from_path(path);
+        let config = JoydbConfig::new(JoydbMode::ReadOnly { adapter });
         Self::open_with_config(config)
     }
 }
@@ -60,9 +84,7 @@ impl<S: State, A: Adapter> Joydb<S, A> {
     /// This database is not persisted to the file system.
     /// This is intended to be used mostly in tests.
     pub fn new_in_memory() -> Result<Self, JoydbError> {
-        let config = JoydbConfig {
-            mode: JoydbMode::InMemory,
-        };
+        let config = JoydbConfig::new(JoydbMode::InMemory);
         Self::open_with_config(config)
     }
 
@@ -92,6 +114,36 @@ impl<S: State, A: Adapter> Joydb<S, A> {
         self.inner.lock().unwrap().insert(model)
     }
 
+    /// Inserts a new record with an id generated according to the model's [AutoId] strategy.
+    /// The id of the given record is ignored.
+    /// Returns the generated id.
+    ///
+    /// ```
+    /// # use joydb::{Joydb, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    /// struct Comment {
+    ///     #[joydb(id, auto = "increment")]
+    ///     id: u32,
+    ///     text: String,
+    /// }
+    /// # joydb::state! { AppState, models: [Comment] }
+    /// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+    ///
+    /// let comment = Comment { id: 0, text: "First!".to_string() };
+    /// assert_eq!(db.insert_new(&comment).unwrap(), 1);
+    /// assert_eq!(db.insert_new(&comment).unwrap(), 2);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if a unique constraint is violated or the generator has run out of ids.
+    pub fn insert_new<M: AutoId>(&self, model: &M) -> Result<M::Id, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.lock().unwrap().insert_new(model)
+    }
+
     /// Finds a record by its id.
     /// Returns `None` if the record is not found.
     pub fn get<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
@@ -103,6 +155,7 @@ impl<S: State, A: Adapter> Joydb<S, A> {
 
     /// Returns all records that corresponds to the model type.
     /// The order of the records is not guaranteed and is a subject to change in the future versions.
+    /// Use [`query`](Self::query) to get the records in a particular order.
     pub fn get_all<M: Model>(&self) -> Result<Vec<M>, JoydbError>
     where
         S: GetRelation<M>,
@@ -120,6 +173,45 @@ impl<S: State, A: Adapter> Joydb<S, A> {
         self.inner.lock().unwrap().get_all_by(predicate)
     }
 
+    /// Returns all records which have the indexed field equal to the given value.
+    /// Unlike [`get_all_by`](Self::get_all_by), it does not scan the entire relation.
+    ///
+    /// Indexes are declared by marking fields with `#[joydb(index)]`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use joydb::{Joydb, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    /// struct Post {
+    ///     id: u32,
+    ///     #[joydb(index)]
+    ///     user_id: u32,
+    /// }
+    /// # joydb::state! { AppState, models: [Post] }
+    /// # type Db = Joydb<AppState, JsonAdapter>;
+    /// # let db = Db::new_in_memory().unwrap();
+    /// db.insert(&Post { id: 1, user_id: 7 }).unwrap();
+    /// db.insert(&Post { id: 2, user_id: 8 }).unwrap();
+    ///
+    /// let posts = db.get_all_by_index::<Post, _>(PostIndex::UserId, &7).unwrap();
+    /// assert_eq!(posts.len(), 1);
+    /// assert_eq!(posts[0].id, 1);
+    /// ```
+    pub fn get_all_by_index<M, V>(
+        &self,
+        index: Index<M, V>,
+        value: &V,
+    ) -> Result<Vec<M>, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        V: PartialEq + Hash,
+    {
+        self.inner.lock().unwrap().get_all_by_index(index, value)
+    }
+
     /// Returns the number of records that corresponds to the model type.
     ///
     /// # Errors
@@ -134,6 +226,70 @@ impl<S: State, A: Adapter> Joydb<S, A> {
         self.inner.lock().unwrap().count()
     }
 
+    /// Returns the number of records that match the predicate.
+    pub fn count_by<M, F>(&self, predicate: F) -> Result<usize, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        self.inner.lock().unwrap().count_by(predicate)
+    }
+
+    /// Checks if there is at least one record that matches the predicate.
+    pub fn exists_by<M, F>(&self, predicate: F) -> Result<bool, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        self.inner.lock().unwrap().exists_by(predicate)
+    }
+
+    /// Gives the closure read access to all the records of the model without cloning them
+    /// and returns the result of the closure.
+    /// The order of the records is not guaranteed.
+    /// Soft deleted records are included, they can be told apart with [Model::is_deleted].
+    ///
+    /// The database is locked while the closure runs, so calling the database from within the
+    /// closure leads to a deadlock.
+    ///
+    /// ```
+    /// # use joydb::{Joydb, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// # #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    /// # struct Order {
+    /// #     id: u32,
+    /// #     total: u64,
+    /// # }
+    /// # joydb::state! { AppState, models: [Order] }
+    /// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+    /// db.insert(&Order { id: 1, total: 100 }).unwrap();
+    /// db.insert(&Order { id: 2, total: 250 }).unwrap();
+    ///
+    /// let revenue: u64 = db
+    ///     .with_relation(|orders: &[Order]| orders.iter().map(|order| order.total).sum())
+    ///     .unwrap();
+    /// assert_eq!(revenue, 350);
+    /// ```
+    pub fn with_relation<M, R, F>(&self, f: F) -> Result<R, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: FnOnce(&[M]) -> R,
+    {
+        self.inner.lock().unwrap().with_relation(f)
+    }
+
+    /// Starts a query to fetch filtered, ordered and paginated records.
+    /// See [Query] for details.
+    pub fn query<M: Model>(&self) -> Query<'_, S, A, M>
+    where
+        S: GetRelation<M>,
+    {
+        Query::new(self)
+    }
+
     pub fn update<M: Model>(&self, new_record: &M) -> Result<(), JoydbError>
     where
         S: GetRelation<M>,
@@ -141,9 +297,59 @@ impl<S: State, A: Adapter> Joydb<S, A> {
         self.inner.lock().unwrap().update(new_record)
     }
 
+    /// Modifies the record with the given id in place.
+    /// The relation is marked as changed only if the mutator actually changes the record.
+    ///
+    /// ```
+    /// # use joydb::{Joydb, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// # #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+    /// # struct Counter {
+    /// #     id: u32,
+    /// #     value: u32,
+    /// # }
+    /// # joydb::state! { AppState, models: [Counter] }
+    /// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+    /// db.insert(&Counter { id: 1, value: 0 }).unwrap();
+    ///
+    /// db.update_with::<Counter, _>(&1, |counter| counter.value += 1).unwrap();
+    ///
+    /// assert_eq!(db.get::<Counter>(&1).unwrap().unwrap().value, 1);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the record is not found or the changed record violates a unique constraint.
+    pub fn update_with<M, F>(&self, id: &M::Id, mutator: F) -> Result<(), JoydbError>
+    where
+        M: Model + PartialEq,
+        S: GetRelation<M>,
+        F: FnOnce(&mut M),
+    {
+        self.inner.lock().unwrap().update_with(id, mutator)
+    }
+
+    /// Modifies all the records that match the predicate in place.
+    /// Returns the number of records that were actually changed.
+    ///
+    /// # Errors
+    /// Returns an error if a changed record violates a unique constraint.
+    /// The records changed before the error stay changed, use [`transaction`](Self::transaction)
+    /// to roll them back.
+    pub fn update_all_by<M, P, F>(&self, predicate: P, mutator: F) -> Result<usize, JoydbError>
+    where
+        M: Model + PartialEq,
+        S: GetRelation<M>,
+        P: Fn(&M) -> bool,
+        F: Fn(&mut M),
+    {
+        self.inner.lock().unwrap().update_all_by(predicate, mutator)
+    }
+
     /// Upserts a record.
     /// If the record with the same id already exists, it will be updated.
     /// Otherwise, it will be inserted.
+    /// Returns [JoydbError::DuplicatedId] if the id belongs to a soft deleted record,
+    /// which can be brought back only with [`restore`](Self::restore).
     pub fn upsert<M: Model>(&self, record: &M) -> Result<(), JoydbError>
     where
         S: GetRelation<M>,
@@ -153,6 +359,34 @@ impl<S: State, A: Adapter> Joydb<S, A> {
 
     /// Deletes a record by its id and returns the deleted record.
     /// If the record is not found, it returns `None`.
+    ///
+    /// If the model has a field marked with `#[joydb(soft_delete)]`, the record is not removed,
+    /// but only marked as deleted. Soft deleted records are excluded from all the reads
+    /// (except [`with_relation`](Self::with_relation)) and can not be updated,
+    /// but they can be fetched with [`get_with_deleted`](Self::get_with_deleted),
+    /// brought back with [`restore`](Self::restore) or removed with [`purge`](Self::purge).
+    /// They also release the values of their unique fields.
+    ///
+    /// ```
+    /// # use joydb::{Joydb, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    /// struct Invoice {
+    ///     id: u32,
+    ///     #[joydb(soft_delete)]
+    ///     deleted: bool,
+    /// }
+    /// # joydb::state! { AppState, models: [Invoice] }
+    /// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+    /// db.insert(&Invoice { id: 1, deleted: false }).unwrap();
+    /// db.delete::<Invoice>(&1).unwrap();
+    ///
+    /// assert!(db.get::<Invoice>(&1).unwrap().is_none());
+    /// assert!(db.get_with_deleted::<Invoice>(&1).unwrap().unwrap().deleted);
+    ///
+    /// db.restore::<Invoice>(&1).unwrap();
+    /// assert!(db.get::<Invoice>(&1).unwrap().is_some());
+    /// ```
     pub fn delete<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
     where
         S: GetRelation<M>,
@@ -162,6 +396,7 @@ impl<S: State, A: Adapter> Joydb<S, A> {
 
     /// Deletes all records that match the predicate.
     /// Returns the deleted records.
+    /// Records of a model with soft delete are only marked as deleted, see [`delete`](Self::delete).
     pub fn delete_all_by<M, F>(&self, predicate: F) -> Result<Vec<M>, JoydbError>
     where
         M: Model,
@@ -171,52 +406,311 @@ impl<S: State, A: Adapter> Joydb<S, A> {
         self.inner.lock().unwrap().delete_all_by(predicate)
     }
 
+    /// Finds a record by its id, including a soft deleted one.
+    /// Returns `None` if the record is not found.
+    pub fn get_with_deleted<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.lock().unwrap().get_with_deleted(id)
+    }
+
+    /// Restores a soft deleted record and returns it.
+    /// Returns `None` if there is no soft deleted record with the given id.
+    /// Returns an error if another record has taken the value of its unique field meanwhile.
+    pub fn restore<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.lock().unwrap().restore(id)
+    }
+
+    /// Physically removes a record by its id, no matter if it's soft deleted or not,
+    /// and returns the removed record.
+    /// For a model without soft delete it's the same as [`delete`](Self::delete).
+    pub fn purge<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.lock().unwrap().purge(id)
+    }
+
+    /// Runs the closure within a transaction.
+    ///
+    /// The database is locked for the entire duration of the closure, so no other thread can
+    /// observe or interleave with the intermediate changes.
+    /// If the closure returns `Ok`, the changes are committed and flushed at once
+    /// (with [SyncPolicy::Instant]).
+    /// If the closure returns `Err` (or panics), all relations modified within the transaction
+    /// are restored to the state they had before the transaction.
+    ///
+    /// The database must not be accessed through [Joydb] from within the closure, since
+    /// this would cause a deadlock. Use the given [Transaction] instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use joydb::{Joydb, JoydbError, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// # #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    /// # struct Customer { id: u32, balance: u32 }
+    /// # #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    /// # struct Order { id: u32, customer_id: u32, price: u32 }
+    /// # joydb::state! { AppState, models: [Customer, Order] }
+    /// # type Db = Joydb<AppState, JsonAdapter>;
+    /// # let db = Db::new_in_memory().unwrap();
+    /// # db.insert(&Customer { id: 1, balance: 100 }).unwrap();
+    /// let order = Order { id: 1, customer_id: 1, price: 30 };
+    ///
+    /// db.transaction(|tx| {
+    ///     tx.insert(&order)?;
+    ///     let mut customer: Customer = tx.get(&order.customer_id)?.unwrap();
+    ///     customer.balance -= order.price;
+    ///     tx.update(&customer)?;
+    ///     Ok::<_, JoydbError>(())
+    /// })
+    /// .unwrap();
+    /// # assert_eq!(db.get::<Customer>(&1).unwrap().unwrap().balance, 70);
+    /// ```
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction<'_, S, A>) -> Result<T, E>,
+        E: From<JoydbError>,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        let mut tx = Transaction::new(&mut inner);
+        let value = f(&mut tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
     /// Flushes the state to the file system.
     /// If there are any unsaved changes the corresponding file(s) will be rewritten from scratch.
     /// This method is also always called automatically on drop.
+    ///
+    /// # Errors
+    /// Returns [JoydbError::ModifiedExternally] if the data were modified by another program
+    /// and [detect_external_changes](JoydbConfig::detect_external_changes) is enabled.
     pub fn flush(&self) -> Result<(), JoydbError> {
         self.inner.lock().unwrap().flush()
     }
+
+    /// Loads the state from the file system again, replacing the state in memory.
+    /// It's useful to pick up changes made to the data by other programs.
+    /// In in-memory mode it does nothing.
+    ///
+    /// # Errors
+    /// Returns [JoydbError::UnsavedChanges] if there are changes that are not flushed yet.
+    /// Use [`force_reload`](Self::force_reload) to discard them.
+    pub fn reload(&self) -> Result<(), JoydbError> {
+        self.inner.lock().unwrap().reload(false)
+    }
+
+    /// Same as [`reload`](Self::reload), but discards the changes that are not flushed yet.
+    pub fn force_reload(&self) -> Result<(), JoydbError> {
+        self.inner.lock().unwrap().reload(true)
+    }
+
+    /// Subscribes to the changes of the records of the model.
+    /// Returns a receiver, which gets a [Change] for every inserted, updated or deleted record
+    /// right after the modification (or after a transaction is committed).
+    /// With [SyncPolicy::Instant] the change is sent only after it's flushed, so the changes
+    /// of a transaction, which failed to flush and was rolled back, are never sent.
+    ///
+    /// Dropping the receiver unsubscribes.
+    ///
+    /// ```
+    /// # use joydb::{Change, Joydb, Model, adapters::JsonAdapter};
+    /// # use serde::{Serialize, Deserialize};
+    /// # #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+    /// # struct Todo {
+    /// #     id: u32,
+    /// #     done: bool,
+    /// # }
+    /// # joydb::state! { AppState, models: [Todo] }
+    /// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+    /// let changes = db.subscribe::<Todo>();
+    ///
+    /// let todo = Todo { id: 1, done: false };
+    /// db.insert(&todo).unwrap();
+    /// db.delete::<Todo>(&1).unwrap();
+    ///
+    /// assert_eq!(changes.try_recv(), Ok(Change::Inserted(todo.clone())));
+    /// assert_eq!(changes.try_recv(), Ok(Change::Deleted(todo)));
+    /// ```
+    pub fn subscribe<M: Model + Send>(&self) -> Receiver<Change<M>>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.lock().unwrap().subscribe()
+    }
 }
 
 #[derive(Debug)]
-struct InnerJoydb<S: State, A: Adapter> {
+pub(crate) struct InnerJoydb<S: State, A: Adapter> {
     state: S,
     mode: JoydbMode<A>,
+
+    /// Fingerprint of the data taken when they were last loaded or written.
+    /// It's `None` unless the detection of external changes is enabled.
+    fingerprint: Option<Fingerprint>,
+
+    subscribers: Subscribers,
+
+    clock: Arc<dyn Clock>,
+
+    /// Prevents other processes from opening the same data.
+    /// It's declared last to be released only after the final flush on drop.
+    _lock: Option<FileLock>,
 }
 
 impl<S: State, A: Adapter> InnerJoydb<S, A> {
     fn open_with_config(config: JoydbConfig<A>) -> Result<Self, JoydbError> {
-        let JoydbConfig { mode } = config;
+        let JoydbConfig {
+            mode,
+            detect_external_changes,
+            clock,
+        } = config;
+
+        // Take the lock before loading, so the loaded state can not be changed by another process
+        let lock = match &mode {
+            JoydbMode::Persistent { adapter, .. } => {
+                adapter.path().map(FileLock::exclusive).transpose()?
+            }
+            JoydbMode::ReadOnly { adapter } => {
+                adapter.path().map(FileLock::shared).transpose()?.flatten()
+            }
+            JoydbMode::InMemory => None,
+        };
+
+        let state = Self::load_state(&mode, &clock)?;
+
+        let fingerprint = match &mode {
+            JoydbMode::Persistent { adapter, .. }
+                if detect_external_changes && adapter.path().is_some() =>
+            {
+                Some(Fingerprint::of(&adapter.data_paths())?)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            state,
+            mode,
+            fingerprint,
+            subscribers: Subscribers::default(),
+            clock,
+            _lock: lock,
+        })
+    }
 
-        // Get the initial state
-        let state = match &mode {
+    /// Loads the state with the adapter (or creates an empty one in in-memory mode).
+    fn load_state(mode: &JoydbMode<A>, clock: &Arc<dyn Clock>) -> Result<S, JoydbError> {
+        let mut state = match mode {
             JoydbMode::Persistent {
                 adapter,
                 sync_policy: _,
             } => adapter.load_state::<S>()?,
+            JoydbMode::ReadOnly { adapter } => {
+                // Adapters create missing files on load, so it must not be reached
+                if let Some(path) = adapter.path()
+                    && !path.exists()
+                {
+                    let message = format!("{} does not exist", path.display());
+                    return Err(io::Error::new(io::ErrorKind::NotFound, message).into());
+                }
+                let mut state = adapter.load_state_read_only::<S>()?;
+                // Migrated data are marked dirty to be upgraded on flush, which never happens
+                // in read-only mode, so they must not count as unsaved changes
+                state.reset_dirty();
+                state
+            }
             JoydbMode::InMemory => S::default(),
         };
 
-        Ok(Self { state, mode })
+        if matches!(mode, JoydbMode::Persistent { .. }) && A::RECORDS_CHANGES {
+            state.visit_relations_mut(&mut EnableChangeTracking)?;
+        }
+        state.visit_relations_mut(&mut SetClock(clock))?;
+
+        Ok(state)
+    }
+
+    /// Replaces the state with the one loaded from the file system.
+    fn reload(&mut self, force: bool) -> Result<(), JoydbError> {
+        if matches!(self.mode, JoydbMode::InMemory) {
+            return Ok(());
+        }
+        if self.is_dirty() && !force {
+            return Err(JoydbError::UnsavedChanges);
+        }
+
+        self.state = Self::load_state(&self.mode, &self.clock)?;
+        self.state
+            .visit_relations_mut(&mut EnableSubscribedChangeTracking {
+                subscribers: &self.subscribers,
+            })?;
+        self.update_fingerprint()?;
+        Ok(())
+    }
+
+    fn subscribe<M: Model + Send>(&mut self) -> Receiver<Change<M>>
+    where
+        S: GetRelation<M>,
+    {
+        <S as GetRelation<M>>::get_relation_mut(&mut self.state).enable_change_tracking();
+        self.subscribers.subscribe()
     }
 
     /// Write data to the file system if there are unsaved changes.
     fn flush(&mut self) -> Result<(), JoydbError> {
         if self.is_dirty() {
+            self.check_external_changes()?;
             self.write_state()?;
             self.state.reset_dirty();
+            self.update_fingerprint()?;
+        }
+        Ok(())
+    }
+
+    /// Makes sure the data were not modified by another program since they were loaded
+    /// or written, if the detection of external changes is enabled.
+    fn check_external_changes(&self) -> Result<(), JoydbError> {
+        if let Some(fingerprint) = &self.fingerprint
+            && let Some(adapter) = self.adapter()
+            && let Some(path) = adapter.path()
+            && Fingerprint::of(&adapter.data_paths())? != *fingerprint
+        {
+            return Err(JoydbError::ModifiedExternally(path.to_path_buf()));
         }
         Ok(())
     }
 
+    fn update_fingerprint(&mut self) -> Result<(), JoydbError> {
+        if self.fingerprint.is_some()
+            && let Some(adapter) = self.adapter()
+        {
+            self.fingerprint = Some(Fingerprint::of(&adapter.data_paths())?);
+        }
+        Ok(())
+    }
+
+    fn adapter(&self) -> Option<&A> {
+        match &self.mode {
+            JoydbMode::Persistent { adapter, .. } | JoydbMode::ReadOnly { adapter } => {
+                Some(adapter)
+            }
+            JoydbMode::InMemory => None,
+        }
+    }
+
     fn write_state(&mut self) -> Result<(), JoydbError> {
         match &self.mode {
             JoydbMode::Persistent {
                 adapter,
                 sync_policy: _,
             } => adapter.write_state(&self.state),
-            JoydbMode::InMemory => {
+            JoydbMode::ReadOnly { .. } | JoydbMode::InMemory => {
                 // Do nothing
                 Ok(())
             }
@@ -227,15 +721,26 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
         self.state.is_dirty()
     }
 
-    fn get_relation_mut<M: Model>(&mut self) -> &mut Relation<M>
+    pub(crate) fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    /// Returns a relation for modification.
+    ///
+    /// # Errors
+    /// Returns [JoydbError::ReadOnly] if the database is opened in read-only mode.
+    pub(crate) fn get_relation_mut<M: Model>(&mut self) -> Result<&mut Relation<M>, JoydbError>
     where
         S: GetRelation<M>,
     {
+        if matches!(self.mode, JoydbMode::ReadOnly { .. }) {
+            return Err(JoydbError::ReadOnly);
+        }
         let state = &mut self.state;
-        <S as GetRelation<M>>::get_relation_mut(state)
+        Ok(<S as GetRelation<M>>::get_relation_mut(state))
     }
 
-    fn get_relation<M: Model>(&self) -> &Relation<M>
+    pub(crate) fn get_relation<M: Model>(&self) -> &Relation<M>
     where
         S: GetRelation<M>,
     {
@@ -247,13 +752,24 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
     where
         S: GetRelation<M>,
     {
-        let relation = self.get_relation_mut::<M>();
+        let relation = self.get_relation_mut::<M>()?;
         relation.insert(model)?;
         self.after_change()?;
         Ok(())
     }
 
-    fn get<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    fn insert_new<M: AutoId>(&mut self, model: &M) -> Result<M::Id, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        // Fail if the relation can not be modified
+        self.get_relation_mut::<M>()?;
+        let id = auto_id::insert_new(&mut self.state, model)?;
+        self.after_change()?;
+        Ok(id)
+    }
+
+    pub(crate) fn get<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
     where
         S: GetRelation<M>,
     {
@@ -261,7 +777,7 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
         relation.get(id)
     }
 
-    fn get_all<M: Model>(&self) -> Result<Vec<M>, JoydbError>
+    pub(crate) fn get_all<M: Model>(&self) -> Result<Vec<M>, JoydbError>
     where
         S: GetRelation<M>,
     {
@@ -280,7 +796,21 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
         relation.get_all_by(predicate)
     }
 
-    pub fn count<M: Model>(&self) -> Result<usize, JoydbError>
+    pub(crate) fn get_all_by_index<M, V>(
+        &self,
+        index: Index<M, V>,
+        value: &V,
+    ) -> Result<Vec<M>, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        V: PartialEq + Hash,
+    {
+        let relation = self.get_relation::<M>();
+        relation.get_all_by_index(index, value)
+    }
+
+    pub(crate) fn count<M: Model>(&self) -> Result<usize, JoydbError>
     where
         S: GetRelation<M>,
     {
@@ -288,21 +818,88 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
         relation.count()
     }
 
+    pub(crate) fn get_with_deleted<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        let relation = self.get_relation::<M>();
+        relation.get_with_deleted(id)
+    }
+
+    pub(crate) fn count_by<M, F>(&self, predicate: F) -> Result<usize, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        let relation = self.get_relation::<M>();
+        relation.count_by(predicate)
+    }
+
+    pub(crate) fn exists_by<M, F>(&self, predicate: F) -> Result<bool, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        let relation = self.get_relation::<M>();
+        relation.exists_by(predicate)
+    }
+
+    pub(crate) fn with_relation<M, R, F>(&self, f: F) -> Result<R, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: FnOnce(&[M]) -> R,
+    {
+        let relation = self.get_relation::<M>();
+        Ok(f(relation.records()))
+    }
+
     fn update<M: Model>(&mut self, new_record: &M) -> Result<(), JoydbError>
     where
         S: GetRelation<M>,
     {
-        let relation = self.get_relation_mut::<M>();
+        let relation = self.get_relation_mut::<M>()?;
         relation.update(new_record)?;
         self.after_change()?;
         Ok(())
     }
 
+    fn update_with<M, F>(&mut self, id: &M::Id, mutator: F) -> Result<(), JoydbError>
+    where
+        M: Model + PartialEq,
+        S: GetRelation<M>,
+        F: FnOnce(&mut M),
+    {
+        let relation = self.get_relation_mut::<M>()?;
+        if relation.update_with(id, mutator)? {
+            self.after_change()?;
+        }
+        Ok(())
+    }
+
+    fn update_all_by<M, P, F>(&mut self, predicate: P, mutator: F) -> Result<usize, JoydbError>
+    where
+        M: Model + PartialEq,
+        S: GetRelation<M>,
+        P: Fn(&M) -> bool,
+        F: Fn(&mut M),
+    {
+        let relation = self.get_relation_mut::<M>()?;
+        let result = relation.update_all_by(predicate, mutator);
+        // The records changed before an error stay changed, so they must be synced as well.
+        if relation.is_dirty() {
+            self.after_change()?;
+        }
+        result
+    }
+
     fn upsert<M: Model>(&mut self, record: &M) -> Result<(), JoydbError>
     where
         S: GetRelation<M>,
     {
-        let relation = self.get_relation_mut::<M>();
+        let relation = self.get_relation_mut::<M>()?;
         relation.upsert(record)?;
         self.after_change()?;
         Ok(())
@@ -312,7 +909,7 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
     where
         S: GetRelation<M>,
     {
-        let relation = self.get_relation_mut::<M>();
+        let relation = self.get_relation_mut::<M>()?;
         let maybe_deleted_record = relation.delete(id)?;
         if maybe_deleted_record.is_some() {
             self.after_change()?;
@@ -320,13 +917,37 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
         Ok(maybe_deleted_record)
     }
 
+    fn restore<M: Model>(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        let relation = self.get_relation_mut::<M>()?;
+        let maybe_restored_record = relation.restore(id)?;
+        if maybe_restored_record.is_some() {
+            self.after_change()?;
+        }
+        Ok(maybe_restored_record)
+    }
+
+    fn purge<M: Model>(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        let relation = self.get_relation_mut::<M>()?;
+        let maybe_purged_record = relation.purge(id)?;
+        if maybe_purged_record.is_some() {
+            self.after_change()?;
+        }
+        Ok(maybe_purged_record)
+    }
+
     pub fn delete_all_by<M, F>(&mut self, predicate: F) -> Result<Vec<M>, JoydbError>
     where
         M: Model,
         S: GetRelation<M>,
         F: Fn(&M) -> bool,
     {
-        let relation = self.get_relation_mut::<M>();
+        let relation = self.get_relation_mut::<M>()?;
         let deleted_records = relation.delete_all_by(predicate)?;
         if !deleted_records.is_empty() {
             self.after_change()?;
@@ -335,12 +956,37 @@ impl<S: State, A: Adapter> InnerJoydb<S, A> {
     }
 
     /// Hook which is called every time after database state has changed.
-    fn after_change(&mut self) -> Result<(), JoydbError> {
+    ///
+    /// The adapter receives the changes before the flush, which persists them,
+    /// while the subscribers are notified only after the flush succeeds.
+    /// If the flush fails, the changes are kept for the subscribers until the next flush.
+    pub(crate) fn after_change(&mut self) -> Result<(), JoydbError> {
+        let has_adapter = match self.mode.recording_adapter() {
+            Some(adapter) => {
+                self.state
+                    .visit_relations_mut(&mut ChangeRecorder { adapter })?;
+                true
+            }
+            None => false,
+        };
         if self.mode.is_instant_sync_policy() {
             self.flush()?;
         }
+        if has_adapter || !self.subscribers.is_empty() {
+            self.state.visit_relations_mut(&mut ChangeNotifier {
+                has_adapter,
+                subscribers: &mut self.subscribers,
+            })?;
+        }
         Ok(())
     }
+
+    /// Makes the adapter forget the changes it received, because they are rolled back.
+    pub(crate) fn discard_changes(&self) {
+        if let Some(adapter) = self.mode.recording_adapter() {
+            adapter.discard_changes();
+        }
+    }
 }
 
 impl<S: State, A: Adapter> Drop for InnerJoydb<S, A> {
@@ -368,16 +1014,75 @@ pub enum SyncPolicy {
     Manual,
 }
 
+/// Configuration of the database, see [Joydb::open_with_config].
+///
+/// Besides the mode, all the options have defaults, so a config can be built with
+/// [JoydbConfig::new] and the `with_*` methods, or with a struct literal:
+///
+/// ```
+/// # use joydb::{JoydbConfig, JoydbMode, SyncPolicy, adapters::JsonAdapter};
+/// let config = JoydbConfig {
+///     mode: JoydbMode::Persistent {
+///         adapter: JsonAdapter::new("data.json", false),
+///         sync_policy: SyncPolicy::Manual,
+///     },
+///     ..Default::default()
+/// };
+/// ```
 #[derive(Debug)]
 pub struct JoydbConfig<A: Adapter> {
     pub mode: JoydbMode<A>,
+
+    /// Whether to check that the data were not modified by another program (e.g. edited by hand)
+    /// before every flush. If they were, the flush fails with
+    /// [JoydbError::ModifiedExternally](crate::JoydbError::ModifiedExternally) instead of
+    /// overwriting the changes, and [Joydb::force_reload] can be used to pick them up.
+    /// Disabled by default.
+    ///
+    /// The check compares modification time and size of the data file(s), so it's cheap, but
+    /// it may miss a change made within the resolution of the file system timestamps.
+    /// It has effect only in persistent mode.
+    pub detect_external_changes: bool,
+
+    /// Source of the time for the fields marked with `#[joydb(created_at)]` and
+    /// `#[joydb(updated_at)]`. It's [SystemClock] by default, but a fake clock can be
+    /// used in tests to get predictable timestamps.
+    pub clock: Arc<dyn Clock>,
+}
+
+impl<A: Adapter> Default for JoydbConfig<A> {
+    /// Returns the config of an in-memory database.
+    fn default() -> Self {
+        Self::new(JoydbMode::InMemory)
+    }
 }
 
 impl<A: Adapter> JoydbConfig<A> {
+    /// Creates a config with the given mode and default options.
+    pub fn new(mode: JoydbMode<A>) -> Self {
+        Self {
+            mode,
+            detect_external_changes: false,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets [JoydbConfig::detect_external_changes].
+    pub fn with_detect_external_changes(mut self, detect_external_changes: bool) -> Self {
+        self.detect_external_changes = detect_external_changes;
+        self
+    }
+
+    /// Sets [JoydbConfig::clock].
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     fn sync_policy(&self) -> Option<SyncPolicy> {
         match &self.mode {
             JoydbMode::Persistent { sync_policy, .. } => Some(*sync_policy),
-            JoydbMode::InMemory => None,
+            JoydbMode::ReadOnly { .. } | JoydbMode::InMemory => None,
         }
     }
 }
@@ -387,6 +1092,11 @@ impl<A: Adapter> JoydbConfig<A> {
 #[derive(Debug)]
 pub enum JoydbMode<A: Adapter> {
     /// The data are flushed to the file system.
+    ///
+    /// While the database is open, an exclusive advisory lock is held on a lockfile next to the
+    /// data (e.g. `data.json.lock`), so another process opening the same data gets
+    /// [JoydbError::Locked](crate::JoydbError::Locked) instead of silently overwriting the changes.
+    /// The lock is released when the database is dropped.
     Persistent {
         /// The adapter used to read/write the data (e.g. JSON, CSV, etc.)
         adapter: A,
@@ -394,6 +1104,18 @@ pub enum JoydbMode<A: Adapter> {
         /// Specifies when data must be flushed to the file system.
         sync_policy: SyncPolicy,
     },
+
+    /// The data are loaded from the file system, but never written back.
+    /// Every modification fails with [JoydbError::ReadOnly](crate::JoydbError::ReadOnly)
+    /// and no files are created, so the data must already exist.
+    ///
+    /// A shared lock is held instead of an exclusive one, so multiple readers can open the same
+    /// data at once, but not while it's opened for writing.
+    ReadOnly {
+        /// The adapter used to read the data (e.g. JSON, CSV, etc.)
+        adapter: A,
+    },
+
     /// The data are never flushed to the file system. Even when [Joydb::flush] is explicitly
     /// called.
     /// With this mode, Joydb acts like in-memory-only database and this mode is mostly intended
@@ -405,8 +1127,87 @@ impl<A: Adapter> JoydbMode<A> {
     fn is_instant_sync_policy(&self) -> bool {
         match self {
             JoydbMode::Persistent { sync_policy, .. } => *sync_policy == SyncPolicy::Instant,
-            JoydbMode::InMemory => false,
+            JoydbMode::ReadOnly { .. } | JoydbMode::InMemory => false,
+        }
+    }
+
+    /// Returns the adapter, if it needs to receive the changes of individual records.
+    fn recording_adapter(&self) -> Option<&A> {
+        match self {
+            JoydbMode::Persistent { adapter, .. } if A::RECORDS_CHANGES => Some(adapter),
+            _ => None,
+        }
+    }
+}
+
+/// Enables change tracking in every relation of a state.
+struct EnableChangeTracking;
+
+impl RelationVisitor for EnableChangeTracking {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        relation.enable_change_tracking();
+        Ok(())
+    }
+}
+
+/// Sets the clock of every relation of a state.
+struct SetClock<'a>(&'a Arc<dyn Clock>);
+
+impl RelationVisitor for SetClock<'_> {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        relation.set_clock(Arc::clone(self.0));
+        Ok(())
+    }
+}
+
+/// Enables change tracking in the relations, which models have subscribers.
+struct EnableSubscribedChangeTracking<'a> {
+    subscribers: &'a Subscribers,
+}
+
+impl RelationVisitor for EnableSubscribedChangeTracking<'_> {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        if self.subscribers.is_subscribed::<M>() {
+            relation.enable_change_tracking();
+        }
+        Ok(())
+    }
+}
+
+/// Passes the tracked changes of every relation, which were not passed yet, to the adapter.
+struct ChangeRecorder<'a, A: Adapter> {
+    adapter: &'a A,
+}
+
+impl<A: Adapter> RelationVisitor for ChangeRecorder<'_, A> {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        for change in relation.unrecorded_changes() {
+            self.adapter.record_change(change)?;
         }
+        Ok(())
+    }
+}
+
+/// Takes the tracked changes of every relation and passes them to the subscribers.
+struct ChangeNotifier<'a> {
+    /// Whether the adapter records changes, so the tracking must stay enabled.
+    has_adapter: bool,
+    subscribers: &'a mut Subscribers,
+}
+
+impl RelationVisitor for ChangeNotifier<'_> {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        let changes = relation.take_changes();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let has_subscribers = self.subscribers.notify(&changes);
+        if !has_subscribers && !self.has_adapter {
+            // Nobody needs the changes of the relation anymore
+            relation.disable_change_tracking();
+        }
+        Ok(())
     }
 }
 
@@ -421,11 +1222,15 @@ fn spawn_periodic_sync_thread<S: State, A: Adapter>(
         loop {
             std::thread::sleep(interval);
             if let Some(inner) = weak_inner_db.upgrade() {
-                inner
+                let result = inner
                     .lock()
                     .expect("Failed to lock the Joydb database from the background thread")
-                    .flush()
-                    .expect("Failed to flush the Joydb database from the background thread");
+                    .flush();
+                // The data are flushed again on the next tick, so the thread must not panic
+                // (e.g. if the data were modified externally).
+                if let Err(err) = result {
+                    eprintln!("Failed to flush the database: {}", err);
+                }
             } else {
                 break;
             }
@@ -16,6 +16,27 @@ pub enum JoydbError {
     #[error("{0} is not a directory")]
     NotDirectory(PathBuf),
 
+    /// The data are already opened by another process (or another database instance).
+    /// Contains path of the lockfile.
+    #[error("{0} is locked by another process")]
+    Locked(PathBuf),
+
+    /// Error when trying to modify a database opened in
+    /// [read-only mode](crate::JoydbMode::ReadOnly).
+    #[error("The database is opened in read-only mode")]
+    ReadOnly,
+
+    /// Error when a flush would overwrite data modified by another program since they were
+    /// loaded. Only returned if [JoydbConfig::detect_external_changes](crate::JoydbConfig)
+    /// is enabled. Contains path of the data file or directory.
+    #[error("{0} has been modified externally since it was loaded")]
+    ModifiedExternally(PathBuf),
+
+    /// Error when trying to reload a database which has changes that are not flushed yet.
+    /// Use [Joydb::force_reload](crate::Joydb::force_reload) to discard the changes.
+    #[error("The database has unsaved changes")]
+    UnsavedChanges,
+
     /// Serialization error.
     /// This may occur when adapter format is not supporting the data type.
     /// For example, if you try to serialize a HashMap with K type as a complex structure to JSON.
@@ -36,6 +57,34 @@ pub enum JoydbError {
         model: String,
     },
 
+    /// Error when trying to insert or update a model with a value of a unique field,
+    /// that is already taken by another record.
+    #[error("{model} with {field} = {value} already exists")]
+    UniqueViolation {
+        /// Name of the model (type name)
+        model: String,
+        /// Name of the unique field
+        field: String,
+        /// Value of the field formatted with `Debug`
+        value: String,
+    },
+
+    /// Error when a record does not pass [ModelHooks::validate](crate::ModelHooks::validate).
+    #[error("Invalid {model}: {error}")]
+    Validation {
+        /// Name of the model (type name)
+        model: String,
+        /// Error returned by the validation
+        error: crate::ValidationError,
+    },
+
+    /// Error when an id generator is not able to produce a new id, e.g. on integer overflow.
+    #[error("{model} has run out of ids")]
+    IdsExhausted {
+        /// Name of the model (type name)
+        model: String,
+    },
+
     /// Error when trying to update a model with an ID that does not exist.
     #[error("{model} with id = {id} not found")]
     NotFound {
@@ -0,0 +1,45 @@
+use crate::JoydbError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification time and size of the data files, or of every file in the data directories.
+/// It's used to detect changes made to the data by other programs.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Fingerprint {
+    files: Vec<(PathBuf, Option<SystemTime>, u64)>,
+}
+
+impl Fingerprint {
+    /// Takes a fingerprint of the files or directories at the given paths.
+    /// A missing path has an empty fingerprint.
+    pub(crate) fn of(paths: &[&Path]) -> Result<Self, JoydbError> {
+        let mut files = Vec::new();
+        for path in paths {
+            Self::add(&mut files, path)?;
+        }
+        Ok(Self { files })
+    }
+
+    fn add(
+        files: &mut Vec<(PathBuf, Option<SystemTime>, u64)>,
+        path: &Path,
+    ) -> Result<(), JoydbError> {
+        if path.is_dir() {
+            let start = files.len();
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                if metadata.is_file() {
+                    files.push((entry.path(), metadata.modified().ok(), metadata.len()));
+                }
+            }
+            // The order of directory entries is not guaranteed
+            files[start..].sort();
+        } else if path.exists() {
+            let metadata = fs::metadata(path)?;
+            files.push((path.to_path_buf(), metadata.modified().ok(), metadata.len()));
+        }
+        Ok(())
+    }
+}
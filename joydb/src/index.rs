@@ -0,0 +1,62 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Definition of a secondary index of a model.
+///
+/// It's not meant to be constructed manually: the definitions are generated by
+/// [`derive(Model)`](crate::Model) for every field marked with `#[joydb(index)]`
+/// or `#[joydb(unique)]`.
+pub struct IndexDefinition<M> {
+    /// Name of the indexed field.
+    pub field: &'static str,
+
+    /// Whether two records are not allowed to have equal values of the field.
+    pub unique: bool,
+
+    /// Computes hash of the indexed field of a record.
+    pub hasher: fn(&M) -> u64,
+
+    /// Checks if the indexed fields of two records are equal.
+    pub eq: fn(&M, &M) -> bool,
+
+    /// Formats the indexed field of a record with `Debug` (used in error messages).
+    pub formatter: fn(&M) -> String,
+}
+
+impl<M> IndexDefinition<M> {
+    /// Computes hash of an indexed value.
+    /// It's public only because it's used by the code generated with [`derive(Model)`](crate::Model).
+    pub fn hash_value<V: Hash + ?Sized>(value: &V) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A typed reference to a secondary index of model `M` built on a field of type `V`.
+///
+/// Instances are generated by [`derive(Model)`](crate::Model) as associated constants
+/// of `<Model>Index` type, e.g. `PostIndex::UserId`, and are meant to be passed to
+/// [`Joydb::get_all_by_index`](crate::Joydb::get_all_by_index).
+pub struct Index<M, V> {
+    /// Position of the index in [`Model::indexes`](crate::Model::indexes).
+    pub(crate) position: usize,
+
+    /// Returns value of the indexed field.
+    pub(crate) getter: fn(&M) -> &V,
+}
+
+impl<M, V> Index<M, V> {
+    /// It's public only because it's used by the code generated with [`derive(Model)`](crate::Model).
+    pub const fn new(position: usize, getter: fn(&M) -> &V) -> Self {
+        Self { position, getter }
+    }
+}
+
+// Implement `Clone` and `Copy` manually, otherwise derive would require `M` and `V` to be `Copy`.
+impl<M, V> Clone for Index<M, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M, V> Copy for Index<M, V> {}
@@ -51,19 +51,30 @@
 //! assert_eq!(user.username, "Alice");
 //!
 //! # let _ = ::std::fs::remove_file("data.json");
+//! # let _ = ::std::fs::remove_file("data.json.lock");
 //! ```
 //!
 //! # CRUD operations
 //!
-//! | Operation | Methods                                                                                                      |
-//! |-----------|--------------------------------------------------------------------------------------------------------------|
-//! | Create    | [`insert`](Joydb::insert), [`upsert`](Joydb::upsert)                                                         |
-//! | Read      | [`get`](Joydb::get), [`get_all`](Joydb::get_all), [`get_all_by`](Joydb::get_all_by), [`count`](Joydb::count) |
-//! | Update    | [`update`](Joydb::update), [`upsert`](Joydb::upsert)                                                         |
-//! | Delete    | [`delete`](Joydb::delete), [`delete_all_by`](Joydb::delete_all_by)                                           |
+//! | Operation | Methods                                                                                                                                                                                                                                                                                                                                      |
+//! |-----------|----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------|
+//! | Create    | [`insert`](Joydb::insert), [`insert_new`](Joydb::insert_new), [`upsert`](Joydb::upsert)                                                                                                                                                                                                                                                      |
+//! | Read      | [`get`](Joydb::get), [`get_with_deleted`](Joydb::get_with_deleted), [`get_all`](Joydb::get_all), [`get_all_by`](Joydb::get_all_by), [`get_all_by_index`](Joydb::get_all_by_index), [`count`](Joydb::count), [`count_by`](Joydb::count_by), [`exists_by`](Joydb::exists_by), [`with_relation`](Joydb::with_relation), [`query`](Joydb::query) |
+//! | Update    | [`update`](Joydb::update), [`update_with`](Joydb::update_with), [`update_all_by`](Joydb::update_all_by), [`upsert`](Joydb::upsert)                                                                                                                                                                                                           |
+//! | Delete    | [`delete`](Joydb::delete), [`delete_all_by`](Joydb::delete_all_by), [`purge`](Joydb::purge), [`restore`](Joydb::restore)                                                                                                                                                                                                                     |
 //!
 //! Please refer to [Joydb] for more details.
 //!
+//! # Transactions
+//!
+//! Multiple operations can be grouped with [`transaction`](Joydb::transaction).
+//! If the closure returns an error, all the changes made within the transaction are rolled back.
+//!
+//! # Migrations
+//!
+//! With `migrations` feature enabled, the state can declare migration steps, which upgrade
+//! the persisted data when models change. See [migration] module for more details.
+//!
 //! # Adapters
 //!
 //! There are 2 types of adapters:
@@ -74,13 +85,24 @@
 //! The following adapters are implemented out of the box and can be used with the corresponding
 //! feature flag enabled.
 //!
-//! | Adapter                                                           | Format | Type        | Feature flag |
-//! | ----------------------------------------------------------------- | ------ | ----------- | ------------ |
-//! | [JsonAdapter](crate::adapters::JsonAdapter)                       | JSON   | Unified     | `json`       |
-//! | [JsonPartitionedAdapter](crate::adapters::JsonPartitionedAdapter) | JSON   | Partitioned | `json`       |
-//! | [RonAdapter](crate::adapters::RonAdapter)                         | RON    | Unified     | `ron`        |
-//! | [RonPartitionedAdapter](crate::adapters::RonPartitionedAdapter)   | RON    | Partitioned | `ron`        |
-//! | [CsvAdapter](crate::adapters::CsvAdapter)                         | CSV    | Paritioned  | `csv`        |
+//! | Adapter                                                                         | Format      | Type            | Feature flag |
+//! | ------------------------------------------------------------------------------- | ----------- | --------------- | ------------ |
+//! | [JsonAdapter](crate::adapters::JsonAdapter)                                     | JSON        | Unified         | `json`       |
+//! | [JsonPartitionedAdapter](crate::adapters::JsonPartitionedAdapter)               | JSON        | Partitioned     | `json`       |
+//! | [JsonLogAdapter](crate::adapters::JsonLogAdapter)                               | JSON        | Append-only log | `json`       |
+//! | [RonAdapter](crate::adapters::RonAdapter)                                       | RON         | Unified         | `ron`        |
+//! | [RonPartitionedAdapter](crate::adapters::RonPartitionedAdapter)                 | RON         | Partitioned     | `ron`        |
+//! | [YamlAdapter](crate::adapters::YamlAdapter)                                     | YAML        | Unified         | `yaml`       |
+//! | [YamlPartitionedAdapter](crate::adapters::YamlPartitionedAdapter)               | YAML        | Partitioned     | `yaml`       |
+//! | [TomlAdapter](crate::adapters::TomlAdapter)                                     | TOML        | Unified         | `toml`       |
+//! | [TomlPartitionedAdapter](crate::adapters::TomlPartitionedAdapter)               | TOML        | Partitioned     | `toml`       |
+//! | [MessagePackAdapter](crate::adapters::MessagePackAdapter)                       | MessagePack | Unified         | `msgpack`    |
+//! | [MessagePackPartitionedAdapter](crate::adapters::MessagePackPartitionedAdapter) | MessagePack | Partitioned     | `msgpack`    |
+//! | [CborAdapter](crate::adapters::CborAdapter)                                     | CBOR        | Unified         | `cbor`       |
+//! | [CborPartitionedAdapter](crate::adapters::CborPartitionedAdapter)               | CBOR        | Partitioned     | `cbor`       |
+//! | [BincodeAdapter](crate::adapters::BincodeAdapter)                               | Bincode     | Unified         | `bincode`    |
+//! | [SqliteAdapter](crate::adapters::SqliteAdapter)                                 | SQLite      | Partitioned     | `sqlite`     |
+//! | [CsvAdapter](crate::adapters::CsvAdapter)                                       | CSV         | Paritioned      | `csv`        |
 //!
 //! # Sync policy
 //!
@@ -101,6 +123,9 @@
 //! system at once. This means that it is not suitable for high performance applications or for
 //! domains where the data is too large to fit in memory.
 //!
+//! The [JsonLogAdapter](crate::adapters::JsonLogAdapter) mitigates the write cost by appending only the changes to a log,
+//! but the entire state is still kept in memory.
+//!
 //! It's highly recommended to switch to a proper database like PostgreSQL before Joydb turns into
 //! Paindb.
 //!
@@ -109,18 +134,140 @@
 //! MIT © [Serhii Potapov](https://www.greyblake.com)
 
 pub mod adapters;
+mod auto_id;
+mod change;
 mod db;
 mod error;
+mod fingerprint;
+mod index;
+mod lock;
+#[cfg(feature = "migrations")]
+pub mod migration;
 mod model;
+mod query;
 mod relation;
 mod state;
+mod subscription;
+mod timestamp;
+mod transaction;
 
+pub use auto_id::{AutoId, IdCounter, IdGenerator, Increment};
+#[cfg(feature = "uuid")]
+pub use auto_id::{UuidV4, UuidV7};
+pub use change::{Change, Changeset};
 pub use db::{Joydb, JoydbConfig, JoydbMode, SyncPolicy};
 pub use error::JoydbError;
-pub use model::Model;
+pub use index::{Index, IndexDefinition};
+pub use model::{Hooks, Model, ModelHooks, ValidationError};
+pub use query::Query;
 pub use relation::Relation;
-pub use state::{GetRelation, State};
+#[doc(hidden)]
+pub use state::is_reserved_model_name;
+pub use state::{GetRelation, RelationInspector, RelationVisitor, State};
+pub use timestamp::{Clock, SystemClock, Timestamp};
+pub use transaction::Transaction;
 
 /// A macro to derive the [Model] trait for a struct.
-/// A struct must have a field named `id`, which is the primary key.
+///
+/// By default the field named `id` is the primary key.
+/// A different field can be chosen either with `#[joydb(id)]` on the field
+/// or with `#[joydb(id = "field_name")]` on the struct.
+///
+/// ```
+/// # use joydb::Model;
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// struct Product {
+///     #[joydb(id)]
+///     sku: String,
+///     title: String,
+/// }
+///
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// #[joydb(id = "user_id")]
+/// struct Profile {
+///     user_id: u32,
+///     bio: String,
+/// }
+///
+/// let product = Product { sku: "A-1".to_string(), title: "Apple".to_string() };
+/// assert_eq!(product.id(), "A-1");
+/// ```
+///
+/// If multiple fields are marked with `#[joydb(id)]`, the primary key is composite and
+/// its type is a tuple:
+///
+/// ```
+/// # use joydb::Model;
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// struct Membership {
+///     #[joydb(id)]
+///     user_id: u32,
+///     #[joydb(id)]
+///     group_id: u32,
+/// }
+///
+/// let membership = Membership { user_id: 1, group_id: 2 };
+/// assert_eq!(membership.id(), (1, 2));
+/// ```
+///
+/// A model without a primary key does not compile:
+///
+/// ```compile_fail
+/// # use joydb::Model;
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// struct Note {
+///     text: String,
+/// }
+/// ```
+///
+/// Neither does a model whose `#[joydb(id = "...")]` names a field that does not exist:
+///
+/// ```compile_fail
+/// # use joydb::Model;
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// #[joydb(id = "code")]
+/// struct Country {
+///     name: String,
+/// }
+/// ```
+///
+/// The primary key must be set either on the struct or on the fields, but not on both:
+///
+/// ```compile_fail
+/// # use joydb::Model;
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// #[joydb(id = "code")]
+/// struct Country {
+///     code: String,
+///     #[joydb(id)]
+///     name: String,
+/// }
+/// ```
+///
+/// # Struct attributes
+///
+/// | Attribute                | Description                                                                     |
+/// |--------------------------|---------------------------------------------------------------------------------|
+/// | `#[joydb(id = "field")]` | Uses the given field as primary key.                                            |
+/// | `#[joydb(hooks)]`        | Skips the empty [ModelHooks] implementation, so it can be implemented manually. |
+///
+/// # Field attributes
+///
+/// | Attribute                    | Description                                                                                                                |
+/// |------------------------------|----------------------------------------------------------------------------------------------------------------------------|
+/// | `#[joydb(id)]`               | Uses the field as primary key. Mark multiple fields to get a composite primary key.                                        |
+/// | `#[joydb(id, auto = "...")]` | Generates the id on [`insert_new`](Joydb::insert_new): `increment`, `uuid_v4` or `uuid_v7`, see [AutoId].                  |
+/// | `#[joydb(index)]`            | Maintains a secondary index on the field, see [`get_all_by_index`](Joydb::get_all_by_index).                               |
+/// | `#[joydb(unique)]`           | Rejects records with a value that is already taken, see [`UniqueViolation`](JoydbError::UniqueViolation). Implies `index`. |
+/// | `#[joydb(created_at)]`       | Sets the field to the current time when the record is inserted and keeps it on update, see [Timestamp].                    |
+/// | `#[joydb(updated_at)]`       | Sets the field to the current time when the record is inserted or changed, see [Timestamp].                                |
+/// | `#[joydb(soft_delete)]`      | Marks a `bool` field, which is set instead of removing the record, see [`delete`](Joydb::delete).                          |
+///
+/// For every model with indexed fields, `<Model>Index` type is generated, which exposes
+/// the indexes as associated constants (e.g. `PostIndex::UserId` for `Post::user_id` field).
 pub use joydb_macros::Model;
@@ -0,0 +1,75 @@
+use crate::JoydbError;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// An advisory lock on a lockfile placed next to the data file or directory
+/// (e.g. `data.json.lock` for `data.json`).
+///
+/// The lock is released when the value is dropped. The lockfile itself is left in place:
+/// removing it would race with another process that has just opened it.
+#[derive(Debug)]
+pub(crate) struct FileLock {
+    // The lock is held as long as the file is open
+    _file: File,
+}
+
+impl FileLock {
+    /// Takes an exclusive lock for the data at the given path.
+    /// The lockfile is created if it does not exist yet, along with the parent directory of
+    /// the data (e.g. `data/a` for `data/a/b`), which the adapter expects to exist.
+    ///
+    /// # Errors
+    /// Returns [JoydbError::Locked] if the lock is already held by another process
+    /// (or by another database instance within the same process).
+    pub(crate) fn exclusive(data_path: &Path) -> Result<Self, JoydbError> {
+        let lock_path = lock_path(data_path);
+        if let Some(parent) = lock_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|err| with_path(err, parent))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|err| with_path(err, &lock_path))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self { _file: file }),
+            Err(TryLockError::WouldBlock) => Err(JoydbError::Locked(lock_path)),
+            Err(TryLockError::Error(err)) => Err(with_path(err, &lock_path)),
+        }
+    }
+
+    /// Takes a shared lock for the data at the given path, which allows other readers but no
+    /// writers. Unlike [exclusive](Self::exclusive), it never creates the lockfile: if there
+    /// is none, the data have never been opened for writing and `None` is returned.
+    ///
+    /// # Errors
+    /// Returns [JoydbError::Locked] if an exclusive lock is held by another process.
+    pub(crate) fn shared(data_path: &Path) -> Result<Option<Self>, JoydbError> {
+        let lock_path = lock_path(data_path);
+        let file = match File::open(&lock_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(with_path(err, &lock_path)),
+        };
+        match file.try_lock_shared() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Err(JoydbError::Locked(lock_path)),
+            Err(TryLockError::Error(err)) => Err(with_path(err, &lock_path)),
+        }
+    }
+}
+
+/// Builds path of the lockfile by appending `.lock` to the data path.
+fn lock_path(data_path: &Path) -> PathBuf {
+    // `components()` drops a trailing slash, so `data/` gets `data.lock`, not `data/.lock`
+    let mut lock_path = data_path.components().as_path().as_os_str().to_owned();
+    lock_path.push(".lock");
+    PathBuf::from(lock_path)
+}
+
+/// Adds the path to an IO error, because the error itself does not tell which file failed.
+fn with_path(err: io::Error, path: &Path) -> JoydbError {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display())).into()
+}
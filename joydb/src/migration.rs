@@ -0,0 +1,167 @@
+//! Schema migrations.
+//!
+//! When a model changes in an incompatible way (e.g. a new non-optional field is added),
+//! the data persisted with the previous version can not be deserialized anymore.
+//! Migrations solve it by transforming the raw data before they are deserialized.
+//!
+//! Migrations are declared in the [state!](crate::state) macro as an ordered list of steps.
+//! Every step transforms a single relation from version `N` to `N + 1`,
+//! so the version of the schema is the number of the steps.
+//! The version is stored in the persisted state next to the relations.
+//! Data without a version (e.g. written before the first migration was added) have version `0`.
+//!
+//! On load, all the steps starting from the stored version are applied.
+//! The upgraded data are written on the first flush.
+//!
+//! ```
+//! # let _ = ::std::fs::remove_file("migrations.json");
+//! use joydb::{Joydb, JoydbError, Model, adapters::JsonAdapter, migration::Value};
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+//! struct User {
+//!     id: u32,
+//!     name: String,
+//!     // Added in version 1
+//!     email: String,
+//! }
+//!
+//! fn add_user_email(users: &mut Value) -> Result<(), JoydbError> {
+//!     for user in users.as_array_mut().into_iter().flatten() {
+//!         user["email"] = Value::from("unknown@example.com");
+//!     }
+//!     Ok(())
+//! }
+//!
+//! joydb::state! {
+//!     AppState,
+//!     models: [User],
+//!     migrations: [
+//!         User => add_user_email, // 0 -> 1
+//!     ],
+//! }
+//!
+//! // Data persisted before the email field was added
+//! std::fs::write("migrations.json", r#"{ "User": [{ "id": 1, "name": "Alice" }] }"#).unwrap();
+//!
+//! let db = Joydb::<AppState, JsonAdapter>::open("migrations.json").unwrap();
+//! let alice = db.get::<User>(&1).unwrap().unwrap();
+//! assert_eq!(alice.email, "unknown@example.com");
+//! # drop(db);
+//! # let _ = ::std::fs::remove_file("migrations.json");
+//! # let _ = ::std::fs::remove_file("migrations.json.lock");
+//! ```
+//!
+//! Migrations work only with unified adapters, whose format is self-describing
+//! (e.g. JSON or RON), because the data are deserialized into [Value] first.
+//! Partitioned adapters store every relation separately, so there is no place for the schema
+//! version: opening a state with migrations with a partitioned adapter fails with
+//! [JoydbError::Deserialize].
+//! Records in the log of [JsonLogAdapter](crate::adapters::JsonLogAdapter) are not migrated.
+
+use crate::{JoydbError, Model, Relation, RelationVisitor, State};
+use serde::{Deserialize, Deserializer, de::Error as _};
+
+pub use serde_json::Value;
+
+/// Name of the field that stores the schema version in the persisted state.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A function that transforms the raw data of a relation (usually an array of records).
+pub type MigrateFn = fn(&mut Value) -> Result<(), JoydbError>;
+
+/// A migration step, that transforms a single relation from version `N` to `N + 1`.
+/// It's created by the [state!](crate::state) macro.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    model_name: &'static str,
+    migrate: MigrateFn,
+}
+
+impl Migration {
+    /// Creates a migration step of the relation of the given model.
+    pub fn new(model_name: &'static str, migrate: MigrateFn) -> Self {
+        Self {
+            model_name,
+            migrate,
+        }
+    }
+}
+
+/// Migrates raw data of a state, so the relations can be deserialized.
+///
+/// It's public only because it's used by the code generated with the [state!](crate::state) macro.
+#[doc(hidden)]
+pub struct Migrator {
+    relations: serde_json::Map<String, Value>,
+    is_migrated: bool,
+}
+
+impl Migrator {
+    /// Deserializes raw data of a state and applies the migrations, which are not applied yet.
+    pub fn deserialize<'de, D>(deserializer: D, migrations: &[Migration]) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Value::Object(mut relations) = Value::deserialize(deserializer)? else {
+            return Err(D::Error::custom("expected a map of relations"));
+        };
+
+        let version = match relations.remove(SCHEMA_VERSION_FIELD) {
+            Some(value) => u32::deserialize(value).map_err(D::Error::custom)? as usize,
+            None => 0,
+        };
+        let latest_version = migrations.len();
+        if version > latest_version {
+            return Err(D::Error::custom(format!(
+                "schema version {version} is newer than the latest known version {latest_version}"
+            )));
+        }
+
+        for (from_version, migration) in migrations.iter().enumerate().skip(version) {
+            let relation = relations
+                .entry(migration.model_name)
+                .or_insert_with(|| Value::Array(Vec::new()));
+            (migration.migrate)(relation).map_err(|err| {
+                D::Error::custom(format!(
+                    "failed to migrate {} from version {from_version} to {}: {err}",
+                    migration.model_name,
+                    from_version + 1,
+                ))
+            })?;
+        }
+
+        Ok(Self {
+            relations,
+            is_migrated: version < latest_version,
+        })
+    }
+
+    /// Deserializes a migrated relation. A missing relation is empty.
+    pub fn take_relation<M: Model>(
+        &mut self,
+        model_name: &str,
+    ) -> Result<Relation<M>, serde_json::Error> {
+        match self.relations.remove(model_name) {
+            Some(value) => Relation::deserialize(value),
+            None => Ok(Relation::default()),
+        }
+    }
+
+    /// Marks the state dirty if it was migrated, so it's upgraded on the first flush.
+    pub fn finish<S: State>(self, state: &mut S) -> Result<(), JoydbError> {
+        if self.is_migrated {
+            state.visit_relations_mut(&mut MarkDirty)?;
+        }
+        Ok(())
+    }
+}
+
+struct MarkDirty;
+
+impl RelationVisitor for MarkDirty {
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError> {
+        relation.mark_dirty();
+        Ok(())
+    }
+}
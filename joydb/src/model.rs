@@ -1,11 +1,176 @@
-use serde::{Deserialize, Serialize};
+use crate::IndexDefinition;
+use serde::{Deserialize, Serialize, Serializer, ser::Error as _};
 use std::fmt::Debug;
+use std::hash::Hash;
+use std::time::SystemTime;
 
 /// An identifiable model that can be stored in a database.
-pub trait Model: Clone + Serialize + for<'de> Deserialize<'de> {
-    type Id: Debug + Clone + Eq;
+pub trait Model: Clone + Serialize + for<'de> Deserialize<'de> + 'static {
+    /// Type of the primary key.
+    /// It must implement [Hash], because records are indexed by their ids.
+    type Id: Debug + Clone + Eq + Hash;
 
-    fn id(&self) -> &Self::Id;
+    /// Returns the primary key of the record.
+    ///
+    /// The id is returned by value, because a composite primary key (a tuple) is built from
+    /// multiple fields on the fly.
+    fn id(&self) -> Self::Id;
 
     fn model_name() -> &'static str;
+
+    /// Serializes the primary key, so adapters can use it as a key
+    /// (e.g. a primary key of a SQL table).
+    ///
+    /// With [`derive(Model)`](crate::Model) the id is serialized with its [Serialize]
+    /// implementation. By default it returns an error, so a manual implementation of [Model]
+    /// has to override it only to be used with such adapters
+    /// (e.g. [SqliteAdapter](crate::adapters::SqliteAdapter)).
+    fn serialize_id<S: Serializer>(id: &Self::Id, serializer: S) -> Result<S::Ok, S::Error> {
+        let _ = (id, serializer);
+        let message = format!("id of {} model can not be serialized", Self::model_name());
+        Err(S::Error::custom(message))
+    }
+
+    /// Secondary indexes of the model.
+    ///
+    /// With [`derive(Model)`](crate::Model) the indexes are declared by marking fields with
+    /// `#[joydb(index)]`.
+    fn indexes() -> &'static [IndexDefinition<Self>] {
+        &[]
+    }
+
+    /// Hooks which are called when a record is inserted or updated.
+    ///
+    /// With [`derive(Model)`](crate::Model) these are the [ModelHooks] of the model.
+    /// By default there are no hooks, so a manual implementation of [Model] does not need
+    /// to implement [ModelHooks]. Return [Hooks::from_model_hooks] to use them.
+    fn hooks() -> Hooks<Self> {
+        Hooks::none()
+    }
+
+    /// Sets the field marked with `#[joydb(created_at)]`, if there is one.
+    /// It's called when a new record is inserted.
+    fn set_created_at(&mut self, now: SystemTime) {
+        let _ = now;
+    }
+
+    /// Copies the field marked with `#[joydb(created_at)]` from the given record, if there is one.
+    /// It's called when a record is updated, so the creation time is kept.
+    fn copy_created_at(&mut self, from: &Self) {
+        let _ = from;
+    }
+
+    /// Sets the field marked with `#[joydb(updated_at)]`, if there is one.
+    /// It's called when a record is inserted or updated.
+    fn set_updated_at(&mut self, now: SystemTime) {
+        let _ = now;
+    }
+
+    /// Whether the records are only marked as deleted instead of being removed.
+    ///
+    /// With [`derive(Model)`](crate::Model) it's enabled by marking a `bool` field with
+    /// `#[joydb(soft_delete)]`.
+    fn has_soft_delete() -> bool {
+        false
+    }
+
+    /// Whether the record is soft deleted.
+    fn is_deleted(&self) -> bool {
+        false
+    }
+
+    /// Marks the record as soft deleted or restores it.
+    fn set_deleted(&mut self, deleted: bool) {
+        let _ = deleted;
+    }
+}
+
+/// An error returned by [ModelHooks::validate].
+pub type ValidationError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Hooks which are called every time a record is inserted or updated.
+/// By default they do nothing.
+///
+/// With [`derive(Model)`](crate::Model) an empty implementation is generated, unless the struct
+/// is marked with `#[joydb(hooks)]`, which means the trait is implemented manually:
+///
+/// ```
+/// # use joydb::{Joydb, JoydbError, Model, ModelHooks, ValidationError, adapters::JsonAdapter};
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// #[joydb(hooks)]
+/// struct User {
+///     id: u32,
+///     name: String,
+///     age: u8,
+/// }
+///
+/// impl ModelHooks for User {
+///     fn validate(&self) -> Result<(), ValidationError> {
+///         if self.age > 150 {
+///             return Err("age must not be greater than 150".into());
+///         }
+///         Ok(())
+///     }
+///
+///     fn before_insert(&mut self) {
+///         self.name = self.name.trim().to_string();
+///     }
+/// }
+/// # joydb::state! { AppState, models: [User] }
+/// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+///
+/// let err = db.insert(&User { id: 1, name: "Alice".to_string(), age: 200 }).unwrap_err();
+/// assert!(matches!(err, JoydbError::Validation { .. }));
+/// ```
+pub trait ModelHooks: Sized {
+    /// Checks the invariants of the record.
+    /// It's called before the record is inserted or updated, right after
+    /// [before_insert](Self::before_insert) or [before_update](Self::before_update).
+    /// An error is returned as [JoydbError::Validation](crate::JoydbError::Validation).
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+
+    /// Is called before the record is inserted (including an upsert of a new record).
+    /// It may modify the record, but must not change its id.
+    fn before_insert(&mut self) {}
+
+    /// Is called before the record replaces the `old` one (including an upsert of an existing
+    /// record). It's not called if the record is not changed by
+    /// [Joydb::update_with](crate::Joydb::update_with) or
+    /// [Joydb::update_all_by](crate::Joydb::update_all_by).
+    /// It may modify the record, but must not change its id.
+    fn before_update(&mut self, old: &Self) {
+        let _ = old;
+    }
+}
+
+/// [ModelHooks] of a model as function pointers, see [Model::hooks].
+pub struct Hooks<M> {
+    pub validate: fn(&M) -> Result<(), ValidationError>,
+    pub before_insert: fn(&mut M),
+    pub before_update: fn(&mut M, &M),
+}
+
+impl<M> Hooks<M> {
+    /// Hooks which do nothing.
+    pub fn none() -> Self {
+        Self {
+            validate: |_| Ok(()),
+            before_insert: |_| {},
+            before_update: |_, _| {},
+        }
+    }
+}
+
+impl<M: ModelHooks> Hooks<M> {
+    /// Hooks implemented with [ModelHooks].
+    pub fn from_model_hooks() -> Self {
+        Self {
+            validate: M::validate,
+            before_insert: M::before_insert,
+            before_update: M::before_update,
+        }
+    }
 }
@@ -0,0 +1,164 @@
+use crate::adapters::Adapter;
+use crate::state::{GetRelation, State};
+use crate::{Joydb, JoydbError, Model};
+use std::cmp::{Ordering, Reverse};
+
+type Filter<'a, M> = Box<dyn Fn(&M) -> bool + 'a>;
+/// Ranks the records by an order key, see [rank_by].
+type Ranker<'a, M> = Box<dyn Fn(&[&M]) -> Vec<usize> + 'a>;
+
+/// A query builder to fetch filtered, ordered and paginated records.
+/// Created with [Joydb::query].
+///
+/// Only the records of the requested page are cloned.
+///
+/// ```
+/// # use joydb::{Joydb, Model, adapters::JsonAdapter};
+/// # use serde::{Serialize, Deserialize};
+/// # #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// # struct Todo {
+/// #     id: u32,
+/// #     done: bool,
+/// # }
+/// # joydb::state! { AppState, models: [Todo] }
+/// # let db = Joydb::<AppState, JsonAdapter>::new_in_memory().unwrap();
+/// for id in 1..=10 {
+///     db.insert(&Todo { id, done: id % 2 == 0 }).unwrap();
+/// }
+///
+/// let todos = db
+///     .query::<Todo>()
+///     .filter(|todo| todo.done)
+///     .order_by_desc(|todo| todo.id)
+///     .offset(1)
+///     .limit(2)
+///     .fetch()
+///     .unwrap();
+///
+/// let ids: Vec<u32> = todos.iter().map(|todo| todo.id).collect();
+/// assert_eq!(ids, vec![8, 6]);
+/// ```
+pub struct Query<'a, S: State, A: Adapter, M: Model> {
+    db: &'a Joydb<S, A>,
+    filters: Vec<Filter<'a, M>>,
+    rankers: Vec<Ranker<'a, M>>,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl<'a, S: State, A: Adapter, M: Model> Query<'a, S, A, M> {
+    pub(crate) fn new(db: &'a Joydb<S, A>) -> Self {
+        Self {
+            db,
+            filters: Vec::new(),
+            rankers: Vec::new(),
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    /// Keeps only the records that match the predicate.
+    /// Multiple filters are combined with AND.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&M) -> bool + 'a,
+    {
+        self.filters.push(Box::new(predicate));
+        self
+    }
+
+    /// Orders the records by the key in ascending order.
+    /// Subsequent calls of `order_by` or [`order_by_desc`](Self::order_by_desc) break ties.
+    ///
+    /// Records with equal keys keep their order in the relation, which is not guaranteed.
+    /// So to get stable pages, make sure the keys are unique (e.g. order by id last).
+    pub fn order_by<K, F>(mut self, key: F) -> Self
+    where
+        K: Ord,
+        F: Fn(&M) -> K + 'a,
+    {
+        self.rankers
+            .push(Box::new(move |records| rank_by(records, &key)));
+        self
+    }
+
+    /// Orders the records by the key in descending order.
+    /// See [`order_by`](Self::order_by).
+    pub fn order_by_desc<K, F>(mut self, key: F) -> Self
+    where
+        K: Ord,
+        F: Fn(&M) -> K + 'a,
+    {
+        self.rankers.push(Box::new(move |records| {
+            rank_by(records, |record| Reverse(key(record)))
+        }));
+        self
+    }
+
+    /// Skips the given number of records.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns at most the given number of records.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Executes the query.
+    pub fn fetch(self) -> Result<Vec<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.db.with_relation(|records| self.apply(records))
+    }
+
+    /// Returns the requested page of the records.
+    fn apply(&self, records: &[M]) -> Vec<M> {
+        let matching = records.iter().filter(|record| {
+            !record.is_deleted() && self.filters.iter().all(|filter| filter(record))
+        });
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        if self.rankers.is_empty() {
+            return matching.skip(self.offset).take(limit).cloned().collect();
+        }
+
+        let matching: Vec<&M> = matching.collect();
+        let rankings: Vec<Vec<usize>> = self.rankers.iter().map(|rank| rank(&matching)).collect();
+        let mut positions: Vec<usize> = (0..matching.len()).collect();
+        positions.sort_by(|&a, &b| {
+            rankings
+                .iter()
+                .map(|ranks| ranks[a].cmp(&ranks[b]))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        positions
+            .into_iter()
+            .skip(self.offset)
+            .take(limit)
+            .map(|position| matching[position].clone())
+            .collect()
+    }
+}
+
+/// Returns the rank of every record by the key, equal keys having equal ranks.
+/// The key of every record is computed only once, because it may be expensive (e.g. allocate).
+fn rank_by<M, K: Ord>(records: &[&M], key: impl Fn(&M) -> K) -> Vec<usize> {
+    let keys: Vec<K> = records.iter().map(|record| key(record)).collect();
+    let mut positions: Vec<usize> = (0..keys.len()).collect();
+    positions.sort_by(|&a, &b| keys[a].cmp(&keys[b]));
+
+    let mut ranks = vec![0; keys.len()];
+    let mut rank = 0;
+    for (i, &position) in positions.iter().enumerate() {
+        if i > 0 && keys[positions[i - 1]] != keys[position] {
+            rank += 1;
+        }
+        ranks[position] = rank;
+    }
+    ranks
+}
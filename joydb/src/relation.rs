@@ -1,16 +1,22 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::Arc;
 
-use crate::{JoydbError, Model};
+use crate::{
+    AutoId, Change, Changeset, Clock, Hooks, IdGenerator, Index, IndexDefinition, JoydbError,
+    Model, SystemClock,
+};
 
 /// A relation is a collection of records of a particular model and some metadata.
 /// associated with the relation.
 ///
 /// Relation also implements typical CRUD operations, which are used by the database.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Relation<M: Model> {
     /// Metadata for the relation.
     /// This is not serialized or persisted. They meant to exist only in memory.
-    pub(crate) meta: RelationMeta,
+    pub(crate) meta: RelationMeta<M>,
 
     /// The records in the relation.
     pub(crate) records: Vec<M>,
@@ -39,10 +45,12 @@ impl<M: Model> Relation<M> {
     /// It needs to be public, since it may be used by custom partitioned adapters (.e.g
     /// [crate::adapters::CsvAdapter] uses it).
     pub fn new_with_records(records: Vec<M>) -> Self {
-        Relation {
+        let mut relation = Relation {
             meta: RelationMeta::default(),
             records,
-        }
+        };
+        relation.rebuild_index();
+        relation
     }
 
     /// Is there any unsaved changes?
@@ -57,6 +65,19 @@ impl<M: Model> Relation<M> {
     /// It needs to be public, since it's invoked by the code generated with [crate::state] macro.
     pub fn reset_dirty(&mut self) {
         self.meta.is_dirty = false;
+        self.meta.changeset.clear();
+    }
+
+    /// Marks the relation as modified, so it's written on the next flush.
+    #[allow(dead_code)] // Used only with `migrations` feature
+    pub(crate) fn mark_dirty(&mut self) {
+        self.meta.is_dirty = true;
+    }
+
+    /// Returns ids of the records inserted, updated and deleted since the last flush.
+    /// This is intended to be used only by partitioned adapters.
+    pub fn changeset(&self) -> &Changeset<M::Id> {
+        &self.meta.changeset
     }
 
     /// Returns reference to the records.
@@ -65,28 +86,138 @@ impl<M: Model> Relation<M> {
         &self.records
     }
 
+    /// Returns reference to the record with the given id.
+    /// This is intended to be used only by partitioned adapters.
+    pub fn record(&self, id: &M::Id) -> Option<&M> {
+        self.position(id).map(|position| &self.records[position])
+    }
+
+    /// Rebuilds the id index and the secondary indexes from scratch.
+    /// Must be called every time when `records` are replaced or reordered.
+    fn rebuild_index(&mut self) {
+        self.meta.positions = self
+            .records
+            .iter()
+            .enumerate()
+            .map(|(position, record)| (record.id(), position))
+            .collect();
+
+        self.meta.indexes = RelationMeta::<M>::empty_indexes();
+        for record in &self.records {
+            self.meta.add_to_indexes(record);
+        }
+    }
+
+    /// Returns position of the record with the given id in `records`.
+    pub(crate) fn position(&self, id: &M::Id) -> Option<usize> {
+        self.meta.positions.get(id).copied()
+    }
+
+    /// Returns position of the record with the given id, unless the record is soft deleted.
+    fn visible_position(&self, id: &M::Id) -> Option<usize> {
+        self.position(id)
+            .filter(|&position| !self.records[position].is_deleted())
+    }
+
+    /// Returns the records, which are not soft deleted.
+    fn visible_records(&self) -> impl Iterator<Item = &M> {
+        self.records.iter().filter(|record| !record.is_deleted())
+    }
+
     pub(crate) fn insert(&mut self, record: &M) -> Result<(), JoydbError> {
         let id = record.id();
-        let is_duplicated = self.records.iter().any(|m| m.id() == id);
-        if is_duplicated {
+        if self.meta.positions.contains_key(&id) {
             Err(JoydbError::DuplicatedId {
                 id: format!("{:?}", id),
                 model: M::model_name().to_owned(),
             })
         } else {
-            self.records.push(record.clone());
+            let record = self.prepare_insert(record.clone())?;
+            self.check_unique(&record, &id)?;
+            self.track(|| Change::Inserted(record.clone()));
+            self.push(record);
+            self.meta.changeset.insert(id);
             self.meta.is_dirty = true;
             Ok(())
         }
     }
 
+    /// Inserts a new record with a generated id.
+    /// Ids that are already taken are skipped.
+    ///
+    /// `counter` is the persisted [IdCounter](crate::IdCounter) of the relation, if any.
+    pub(crate) fn insert_new(
+        &mut self,
+        record: &M,
+        counter: Option<u64>,
+    ) -> Result<M::Id, JoydbError>
+    where
+        M: AutoId,
+    {
+        let mut last_id = match self.meta.last_generated_id.take() {
+            Some(id) => Some(id),
+            None => counter
+                .and_then(M::Generator::from_counter)
+                .or_else(|| M::Generator::restore_last_id(self.records.iter().map(M::id))),
+        };
+        let id = loop {
+            let id = M::Generator::generate(last_id.as_ref()).ok_or_else(|| {
+                JoydbError::IdsExhausted {
+                    model: M::model_name().to_owned(),
+                }
+            })?;
+            last_id = Some(id.clone());
+            if !self.meta.positions.contains_key(&id) {
+                break id;
+            }
+        };
+        self.meta.last_generated_id = last_id;
+
+        let mut record = record.clone();
+        record.set_id(id.clone());
+        self.insert(&record)?;
+        Ok(id)
+    }
+
     pub(crate) fn get(&self, id: &M::Id) -> Result<Option<M>, JoydbError> {
-        let maybe_record = self.records.iter().find(|m| m.id() == id).cloned();
+        let maybe_record = self
+            .visible_position(id)
+            .map(|position| self.records[position].clone());
         Ok(maybe_record)
     }
 
+    /// Finds a record by its id, even if it's soft deleted.
+    pub(crate) fn get_with_deleted(&self, id: &M::Id) -> Result<Option<M>, JoydbError> {
+        Ok(self.record(id).cloned())
+    }
+
     pub(crate) fn get_all(&self) -> Result<Vec<M>, JoydbError> {
-        Ok(self.records.to_vec())
+        Ok(self.visible_records().cloned().collect())
+    }
+
+    /// Returns all records which have the indexed field equal to the given value.
+    pub(crate) fn get_all_by_index<V>(
+        &self,
+        index: Index<M, V>,
+        value: &V,
+    ) -> Result<Vec<M>, JoydbError>
+    where
+        V: PartialEq + Hash,
+    {
+        let hash = IndexDefinition::<M>::hash_value(value);
+        let Some(ids) = self.meta.indexes[index.position].get(&hash) else {
+            return Ok(Vec::new());
+        };
+
+        let records = ids
+            .iter()
+            .filter_map(|id| self.position(id))
+            .map(|position| &self.records[position])
+            // Different values may have the same hash, so the values must be compared
+            .filter(|record| (index.getter)(record) == value && !record.is_deleted())
+            .cloned()
+            .collect();
+        Ok(records)
     }
 
     /// Return all records that match the predicate.
@@ -95,8 +226,7 @@ impl<M: Model> Relation<M> {
         F: Fn(&M) -> bool,
     {
         let filtered_records = self
-            .records
-            .iter()
+            .visible_records()
             .filter(|m| predicate(m))
             .cloned()
             .collect();
@@ -104,14 +234,32 @@ impl<M: Model> Relation<M> {
     }
 
     pub(crate) fn count(&self) -> Result<usize, JoydbError> {
-        Ok(self.records.len())
+        Ok(self.visible_records().count())
+    }
+
+    pub(crate) fn count_by<F>(&self, predicate: F) -> Result<usize, JoydbError>
+    where
+        F: Fn(&M) -> bool,
+    {
+        Ok(self.visible_records().filter(|m| predicate(m)).count())
+    }
+
+    pub(crate) fn exists_by<F>(&self, predicate: F) -> Result<bool, JoydbError>
+    where
+        F: Fn(&M) -> bool,
+    {
+        Ok(self.visible_records().any(predicate))
     }
 
     pub(crate) fn update(&mut self, new_record: &M) -> Result<(), JoydbError> {
         let id = new_record.id();
 
-        if let Some(m) = self.records.iter_mut().find(|m| m.id() == id) {
-            *m = new_record.clone();
+        if let Some(position) = self.visible_position(&id) {
+            let new_record = self.prepare_update(new_record.clone(), &self.records[position])?;
+            self.check_unique(&new_record, &id)?;
+            let old_record = self.replace(position, new_record);
+            self.track_update(old_record, position);
+            self.meta.changeset.update(id);
             self.meta.is_dirty = true;
             Ok(())
         } else {
@@ -122,22 +270,133 @@ impl<M: Model> Relation<M> {
         }
     }
 
+    /// Applies the mutator to the record with the given id.
+    /// Returns `false` if the mutator did not change the record.
+    pub(crate) fn update_with<F>(&mut self, id: &M::Id, mutator: F) -> Result<bool, JoydbError>
+    where
+        M: PartialEq,
+        F: FnOnce(&mut M),
+    {
+        let Some(position) = self.visible_position(id) else {
+            return Err(JoydbError::NotFound {
+                id: format!("{:?}", id),
+                model: M::model_name().to_owned(),
+            });
+        };
+        let mut record = self.records[position].clone();
+        mutator(&mut record);
+        self.update_at(position, record)
+    }
+
+    /// Applies the mutator to all the records that match the predicate.
+    /// Returns the number of changed records.
+    ///
+    /// If an error occurs, the records changed before it stay changed.
+    pub(crate) fn update_all_by<P, F>(
+        &mut self,
+        predicate: P,
+        mutator: F,
+    ) -> Result<usize, JoydbError>
+    where
+        M: PartialEq,
+        P: Fn(&M) -> bool,
+        F: Fn(&mut M),
+    {
+        let mut changed_count = 0;
+        for position in 0..self.records.len() {
+            let record = &self.records[position];
+            if !record.is_deleted() && predicate(record) {
+                let mut record = self.records[position].clone();
+                mutator(&mut record);
+                if self.update_at(position, record)? {
+                    changed_count += 1;
+                }
+            }
+        }
+        Ok(changed_count)
+    }
+
     pub(crate) fn upsert(&mut self, record: &M) -> Result<(), JoydbError> {
-        let target_id = record.id();
-        let maybe_target_record = self.records.iter_mut().find(|m| m.id() == target_id);
-        if let Some(target_record) = maybe_target_record {
-            *target_record = record.clone();
+        let id = record.id();
+        let position = self.position(&id);
+        if position.is_some_and(|position| self.records[position].is_deleted()) {
+            // A soft deleted record can be brought back only with `restore`
+            return Err(JoydbError::DuplicatedId {
+                id: format!("{:?}", id),
+                model: M::model_name().to_owned(),
+            });
+        }
+        if let Some(position) = position {
+            let record = self.prepare_update(record.clone(), &self.records[position])?;
+            self.check_unique(&record, &id)?;
+            let old_record = self.replace(position, record);
+            self.track_update(old_record, position);
+            self.meta.changeset.update(id);
         } else {
-            self.records.push(record.clone());
+            let record = self.prepare_insert(record.clone())?;
+            self.check_unique(&record, &id)?;
+            self.track(|| Change::Inserted(record.clone()));
+            self.push(record);
+            self.meta.changeset.insert(id);
         }
         self.meta.is_dirty = true;
         Ok(())
     }
 
+    /// Deletes the record with the given id and returns it.
+    /// If the model supports soft delete, the record is only marked as deleted.
     pub(crate) fn delete(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError> {
-        let index = self.records.iter().position(|m| m.id() == id);
-        if let Some(index) = index {
-            let record = self.records.remove(index);
+        if !M::has_soft_delete() {
+            return self.purge(id);
+        }
+        let Some(position) = self.visible_position(id) else {
+            return Ok(None);
+        };
+        Ok(Some(self.set_deleted_at(position, true)))
+    }
+
+    /// Restores the soft deleted record with the given id and returns it.
+    /// Returns `None` if there is no such record, or an error if the restored record would
+    /// violate a unique constraint.
+    pub(crate) fn restore(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError> {
+        let Some(position) = self.position(id) else {
+            return Ok(None);
+        };
+        if !self.records[position].is_deleted() {
+            return Ok(None);
+        }
+        // The value of a unique field may have been taken while the record was deleted
+        let mut record = self.records[position].clone();
+        record.set_deleted(false);
+        self.check_unique(&record, id)?;
+        Ok(Some(self.set_deleted_at(position, false)))
+    }
+
+    /// Sets the soft delete flag of the record at the given position.
+    /// Returns the changed record.
+    fn set_deleted_at(&mut self, position: usize, deleted: bool) -> M {
+        let mut record = self.records[position].clone();
+        record.set_deleted(deleted);
+        let id = record.id();
+        let old_record = self.replace(position, record);
+        self.track_update(old_record, position);
+        self.meta.changeset.update(id);
+        self.meta.is_dirty = true;
+        self.records[position].clone()
+    }
+
+    /// Physically removes the record with the given id, even if it's soft deleted.
+    pub(crate) fn purge(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError> {
+        if let Some(position) = self.meta.positions.remove(id) {
+            // `swap_remove` is O(1), but it moves the last record into the freed position,
+            // so the index of the moved record must be updated.
+            let record = self.records.swap_remove(position);
+            self.meta.remove_from_indexes(&record);
+            if let Some(moved_record) = self.records.get(position) {
+                self.meta.positions.insert(moved_record.id(), position);
+            }
+            self.track(|| Change::Deleted(record.clone()));
+            self.meta.changeset.delete(id.clone());
             self.meta.is_dirty = true;
             Ok(Some(record))
         } else {
@@ -149,6 +408,17 @@ impl<M: Model> Relation<M> {
     where
         F: Fn(&M) -> bool,
     {
+        if M::has_soft_delete() {
+            let mut deleted_records = Vec::new();
+            for position in 0..self.records.len() {
+                let record = &self.records[position];
+                if !record.is_deleted() && predicate(record) {
+                    deleted_records.push(self.set_deleted_at(position, true));
+                }
+            }
+            return Ok(deleted_records);
+        }
+
         let mut deleted_records = Vec::new();
         let mut retained_records = Vec::with_capacity(self.records.len());
 
@@ -162,15 +432,283 @@ impl<M: Model> Relation<M> {
         }
         self.records = retained_records;
 
+        if !deleted_records.is_empty() {
+            self.rebuild_index();
+        }
+        for record in &deleted_records {
+            self.meta.changeset.delete(record.id());
+        }
+        if self.meta.is_tracking_changes {
+            let changes = deleted_records.iter().cloned().map(Change::Deleted);
+            self.meta.changes.extend(changes);
+        }
+
         Ok(deleted_records)
     }
+
+    /// Makes sure that no other record (a record with id other than `own_id`) has the same
+    /// value of a unique field. Soft deleted records do not hold their values.
+    fn check_unique(&self, record: &M, own_id: &M::Id) -> Result<(), JoydbError> {
+        if record.is_deleted() {
+            return Ok(());
+        }
+        let unique_indexes = M::indexes()
+            .iter()
+            .zip(self.meta.indexes.iter())
+            .filter(|(definition, _)| definition.unique);
+
+        for (definition, index) in unique_indexes {
+            let hash = (definition.hasher)(record);
+            let Some(ids) = index.get(&hash) else {
+                continue;
+            };
+            let is_violated = ids
+                .iter()
+                .filter(|id| *id != own_id)
+                .filter_map(|id| self.position(id))
+                .map(|position| &self.records[position])
+                .any(|other| !other.is_deleted() && (definition.eq)(other, record));
+            if is_violated {
+                return Err(JoydbError::UniqueViolation {
+                    model: M::model_name().to_owned(),
+                    field: definition.field.to_owned(),
+                    value: (definition.formatter)(record),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the record at the given position with its modified version,
+    /// which may also have a different id.
+    /// Returns `false` and does nothing if the modified record is equal to the current one.
+    fn update_at(&mut self, position: usize, record: M) -> Result<bool, JoydbError>
+    where
+        M: PartialEq,
+    {
+        if self.records[position] == record {
+            return Ok(false);
+        }
+        let record = self.prepare_update(record, &self.records[position])?;
+
+        let old_id = self.records[position].id();
+        let new_id = record.id();
+        let is_id_changed = old_id != new_id;
+        if is_id_changed && self.meta.positions.contains_key(&new_id) {
+            return Err(JoydbError::DuplicatedId {
+                id: format!("{:?}", new_id),
+                model: M::model_name().to_owned(),
+            });
+        }
+        self.check_unique(&record, &old_id)?;
+
+        let old_record = self.replace(position, record);
+        if is_id_changed {
+            self.meta.positions.remove(&old_id);
+            self.meta.positions.insert(new_id.clone(), position);
+            self.meta.changeset.delete(old_id);
+            self.meta.changeset.insert(new_id);
+        } else {
+            self.meta.changeset.update(new_id);
+        }
+        self.track_update(old_record, position);
+        self.meta.is_dirty = true;
+        Ok(true)
+    }
+
+    /// Fills the timestamps, runs the hooks and the validation of a record, which is about
+    /// to be inserted.
+    fn prepare_insert(&self, mut record: M) -> Result<M, JoydbError> {
+        let now = self.meta.clock.now();
+        record.set_created_at(now);
+        record.set_updated_at(now);
+        let hooks = M::hooks();
+        (hooks.before_insert)(&mut record);
+        Self::validate(&hooks, &record)?;
+        Ok(record)
+    }
+
+    /// Keeps the creation timestamp, bumps the update timestamp, runs the hooks and the
+    /// validation of a record, which is about to replace the old one.
+    fn prepare_update(&self, mut record: M, old_record: &M) -> Result<M, JoydbError> {
+        record.copy_created_at(old_record);
+        record.set_updated_at(self.meta.clock.now());
+        let hooks = M::hooks();
+        (hooks.before_update)(&mut record, old_record);
+        Self::validate(&hooks, &record)?;
+        Ok(record)
+    }
+
+    fn validate(hooks: &Hooks<M>, record: &M) -> Result<(), JoydbError> {
+        (hooks.validate)(record).map_err(|error| JoydbError::Validation {
+            model: M::model_name().to_owned(),
+            error,
+        })
+    }
+
+    /// Appends a new record and registers it in the index.
+    /// The caller is responsible for checking that the id is not taken yet.
+    fn push(&mut self, record: M) {
+        self.meta.positions.insert(record.id(), self.records.len());
+        self.meta.add_to_indexes(&record);
+        self.records.push(record);
+    }
+
+    /// Replaces the record at the given position keeping the secondary indexes up to date.
+    /// Returns the old record.
+    /// If the new record has a different id, the caller is responsible for updating `positions`.
+    fn replace(&mut self, position: usize, new_record: M) -> M {
+        let old_record = std::mem::replace(&mut self.records[position], new_record);
+        self.meta.remove_from_indexes(&old_record);
+        self.meta.add_to_indexes(&self.records[position]);
+        old_record
+    }
+
+    /// Inserts or replaces the record without checking the constraints.
+    /// It's used to replay changes, that were valid when they were made.
+    // Adapters that use it are behind feature gates.
+    #[allow(dead_code)]
+    pub(crate) fn put(&mut self, record: M) {
+        let id = record.id();
+        if let Some(position) = self.position(&id) {
+            self.replace(position, record);
+            self.meta.changeset.update(id);
+        } else {
+            self.push(record);
+            self.meta.changeset.insert(id);
+        }
+        self.meta.is_dirty = true;
+    }
+
+    /// Records the update of the record at the given position if change tracking is enabled.
+    fn track_update(&mut self, old_record: M, position: usize) {
+        if self.meta.is_tracking_changes {
+            let new_record = self.records[position].clone();
+            self.meta.changes.push(Change::Updated {
+                old: old_record,
+                new: new_record,
+            });
+        }
+    }
+
+    /// Records the change if change tracking is enabled.
+    /// The change is built lazily to avoid cloning records when tracking is disabled.
+    fn track<F>(&mut self, build_change: F)
+    where
+        F: FnOnce() -> Change<M>,
+    {
+        if self.meta.is_tracking_changes {
+            let change = build_change();
+            self.meta.changes.push(change);
+        }
+    }
+
+    /// Enables tracking of changes, which then can be taken with [Relation::take_changes].
+    pub(crate) fn enable_change_tracking(&mut self) {
+        self.meta.is_tracking_changes = true;
+    }
+
+    /// Disables tracking of changes and discards the changes, which are not taken yet.
+    pub(crate) fn disable_change_tracking(&mut self) {
+        self.meta.is_tracking_changes = false;
+        self.meta.changes.clear();
+        self.meta.recorded_changes = 0;
+    }
+
+    /// Returns the changes, which are not returned by the previous call yet.
+    /// Unlike [Relation::take_changes], it keeps the changes.
+    pub(crate) fn unrecorded_changes(&mut self) -> &[Change<M>] {
+        let start = std::mem::replace(&mut self.meta.recorded_changes, self.meta.changes.len());
+        &self.meta.changes[start..]
+    }
+
+    /// Takes the changes made since the last call.
+    pub(crate) fn take_changes(&mut self) -> Vec<Change<M>> {
+        self.meta.recorded_changes = 0;
+        std::mem::take(&mut self.meta.changes)
+    }
+
+    /// Sets the clock used to fill the timestamps of the records.
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.meta.clock = clock;
+    }
 }
 
 /// Metadata for the relation.
 /// It's not serialized or persisted. They meant to exist only in memory.
-#[derive(Debug, Default)]
-pub struct RelationMeta {
+#[derive(Debug, Clone)]
+pub struct RelationMeta<M: Model> {
     pub(crate) is_dirty: bool,
+
+    /// Maps id of a record to its position in [Relation::records].
+    /// It's rebuilt every time a relation is deserialized.
+    pub(crate) positions: HashMap<M::Id, usize>,
+
+    /// Secondary indexes in the same order as [Model::indexes].
+    /// Every index maps hash of the indexed field to ids of the records.
+    pub(crate) indexes: Vec<HashMap<u64, HashSet<M::Id>>>,
+
+    /// The last id generated by [Relation::insert_new].
+    /// It's persisted separately as [IdCounter](crate::IdCounter), from which it's restored
+    /// on the first use (or from the existing records, if there is no counter).
+    pub(crate) last_generated_id: Option<M::Id>,
+
+    /// Whether changes of individual records must be recorded in `changes`.
+    /// It's enabled only if there is a consumer of the changes (e.g. an adapter).
+    pub(crate) is_tracking_changes: bool,
+
+    /// Changes of the records, that are not yet taken with [Relation::take_changes].
+    pub(crate) changes: Vec<Change<M>>,
+
+    /// Number of `changes` already returned by [Relation::unrecorded_changes].
+    pub(crate) recorded_changes: usize,
+
+    /// Ids of the records changed since the last flush.
+    pub(crate) changeset: Changeset<M::Id>,
+
+    /// Source of the time for [Model::set_created_at] and [Model::set_updated_at].
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+impl<M: Model> RelationMeta<M> {
+    fn empty_indexes() -> Vec<HashMap<u64, HashSet<M::Id>>> {
+        M::indexes().iter().map(|_| HashMap::new()).collect()
+    }
+
+    fn add_to_indexes(&mut self, record: &M) {
+        for (definition, index) in M::indexes().iter().zip(self.indexes.iter_mut()) {
+            let hash = (definition.hasher)(record);
+            index.entry(hash).or_default().insert(record.id());
+        }
+    }
+
+    fn remove_from_indexes(&mut self, record: &M) {
+        for (definition, index) in M::indexes().iter().zip(self.indexes.iter_mut()) {
+            let hash = (definition.hasher)(record);
+            if let Some(ids) = index.get_mut(&hash) {
+                ids.remove(&record.id());
+                if ids.is_empty() {
+                    index.remove(&hash);
+                }
+            }
+        }
+    }
+}
+
+impl<M: Model> Default for RelationMeta<M> {
+    fn default() -> Self {
+        Self {
+            is_dirty: false,
+            positions: HashMap::new(),
+            indexes: Self::empty_indexes(),
+            last_generated_id: None,
+            is_tracking_changes: false,
+            changes: Vec::new(),
+            recorded_changes: 0,
+            changeset: Changeset::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
 }
 
 // Custom serialization for Relation
@@ -190,16 +728,14 @@ impl<'de, M: Model> Deserialize<'de> for Relation<M> {
         D: Deserializer<'de>,
     {
         let models = Vec::<M>::deserialize(deserializer)?;
-        Ok(Relation {
-            meta: RelationMeta::default(),
-            records: models,
-        })
+        Ok(Relation::new_with_records(models))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ModelHooks, ValidationError};
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -211,15 +747,28 @@ mod tests {
     impl Model for Post {
         type Id = u32;
 
-        fn id(&self) -> &Self::Id {
-            &self.id
+        fn id(&self) -> Self::Id {
+            self.id
         }
 
         fn model_name() -> &'static str {
             "Post"
         }
+
+        fn indexes() -> &'static [IndexDefinition<Self>] {
+            const INDEXES: &[IndexDefinition<Post>] = &[IndexDefinition {
+                field: "title",
+                unique: false,
+                hasher: |post: &Post| IndexDefinition::<Post>::hash_value(&post.title),
+                eq: |a: &Post, b: &Post| a.title == b.title,
+                formatter: |post: &Post| format!("{:?}", post.title),
+            }];
+            INDEXES
+        }
     }
 
+    const TITLE_INDEX: Index<Post, String> = Index::new(0, |post: &Post| &post.title);
+
     fn sample_posts() -> Vec<Post> {
         vec![first_post(), second_post(), third_post()]
     }
@@ -246,10 +795,7 @@ mod tests {
     }
 
     fn sample_relation() -> Relation<Post> {
-        Relation {
-            meta: RelationMeta { is_dirty: false },
-            records: sample_posts(),
-        }
+        Relation::new_with_records(sample_posts())
     }
 
     mod serialization_and_deserialization {
@@ -257,10 +803,7 @@ mod tests {
 
         #[test]
         fn test_serialize_relation() {
-            let relation = Relation {
-                meta: RelationMeta { is_dirty: false },
-                records: sample_posts(),
-            };
+            let relation = sample_relation();
 
             let json = serde_json::to_string(&relation).unwrap();
             assert_eq!(
@@ -282,21 +825,22 @@ mod tests {
             assert_eq!(relation.records[1].title, "Two");
 
             // The meta field should be default-initialized
-            assert_eq!(relation.meta.is_dirty, false);
+            assert!(!relation.meta.is_dirty);
+
+            // The index must be built
+            assert_eq!(relation.get(&20).unwrap().unwrap().title, "Two");
         }
 
         #[test]
         fn test_serialize_deserialize_roundtrip() {
-            let original = Relation {
-                meta: RelationMeta { is_dirty: true },
-                records: sample_posts(),
-            };
+            let mut original = sample_relation();
+            original.meta.is_dirty = true;
 
             let json = serde_json::to_string(&original).unwrap();
             let deserialized: Relation<Post> = serde_json::from_str(&json).unwrap();
 
             assert_eq!(original.records, deserialized.records);
-            assert_eq!(deserialized.meta.is_dirty, false); // Meta is not serialized
+            assert!(!deserialized.meta.is_dirty); // Meta is not serialized
         }
     }
 
@@ -307,7 +851,7 @@ mod tests {
         fn should_insert_new_record_and_mark_dirty() {
             let mut relation = sample_relation();
             assert_eq!(relation.records.len(), 3);
-            assert_eq!(relation.meta.is_dirty, false);
+            assert!(!relation.meta.is_dirty);
 
             let post = Post {
                 id: 13,
@@ -317,7 +861,7 @@ mod tests {
 
             assert_eq!(relation.records.len(), 4);
             assert_eq!(relation.records[3], post);
-            assert_eq!(relation.meta.is_dirty, true);
+            assert!(relation.meta.is_dirty);
         }
 
         #[test]
@@ -386,6 +930,30 @@ mod tests {
         }
     }
 
+    mod count_by {
+        use super::*;
+
+        #[test]
+        fn should_return_number_of_matching_records() {
+            let relation = sample_relation();
+            let count = relation
+                .count_by(|post| post.title.starts_with('S'))
+                .unwrap();
+            assert_eq!(count, 1);
+        }
+    }
+
+    mod exists_by {
+        use super::*;
+
+        #[test]
+        fn should_check_if_any_record_matches() {
+            let relation = sample_relation();
+            assert!(relation.exists_by(|post| post.title == "Third").unwrap());
+            assert!(!relation.exists_by(|post| post.title == "Fourth").unwrap());
+        }
+    }
+
     mod update {
         use super::*;
 
@@ -400,7 +968,7 @@ mod tests {
 
             let updated_post = relation.get(&2).unwrap().unwrap();
             assert_eq!(updated_post, new_post);
-            assert_eq!(relation.meta.is_dirty, true);
+            assert!(relation.meta.is_dirty);
         }
 
         #[test]
@@ -417,6 +985,171 @@ mod tests {
         }
     }
 
+    mod update_with {
+        use super::*;
+
+        #[test]
+        fn should_update_record_and_mark_dirty() {
+            let mut relation = sample_relation();
+            let is_changed = relation
+                .update_with(&2, |post| post.title = "Updated".to_string())
+                .unwrap();
+
+            assert!(is_changed);
+            assert!(relation.meta.is_dirty);
+            assert_eq!(relation.get(&2).unwrap().unwrap().title, "Updated");
+            assert_eq!(
+                relation
+                    .get_all_by_index(TITLE_INDEX, &"Updated".to_string())
+                    .unwrap()
+                    .len(),
+                1
+            );
+            assert!(
+                relation
+                    .get_all_by_index(TITLE_INDEX, &"Second".to_string())
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        #[test]
+        fn should_not_mark_dirty_when_nothing_changed() {
+            let mut relation = sample_relation();
+            let is_changed = relation
+                .update_with(&2, |post| post.title = "Second".to_string())
+                .unwrap();
+
+            assert!(!is_changed);
+            assert!(!relation.meta.is_dirty);
+        }
+
+        #[test]
+        fn should_allow_to_change_id() {
+            let mut relation = sample_relation();
+            relation.update_with(&1, |post| post.id = 10).unwrap();
+
+            assert!(relation.get(&1).unwrap().is_none());
+            assert_eq!(relation.get(&10).unwrap().unwrap().title, "First");
+        }
+
+        #[test]
+        fn should_return_error_when_new_id_is_taken() {
+            let mut relation = sample_relation();
+            let err = relation.update_with(&1, |post| post.id = 2).unwrap_err();
+
+            assert!(matches!(err, JoydbError::DuplicatedId { .. }));
+            assert_eq!(relation.get(&1).unwrap().unwrap().title, "First");
+        }
+
+        #[test]
+        fn should_return_error_when_record_not_found() {
+            let mut relation = sample_relation();
+            let err = relation.update_with(&999, |_| {}).unwrap_err();
+            assert!(matches!(err, JoydbError::NotFound { .. }));
+        }
+    }
+
+    mod update_all_by {
+        use super::*;
+
+        #[test]
+        fn should_update_matching_records_and_return_number_of_changed() {
+            let mut relation = sample_relation();
+            let changed_count = relation
+                .update_all_by(|post| post.id >= 2, |post| post.title = "Third".to_string())
+                .unwrap();
+
+            // The third post already has the title
+            assert_eq!(changed_count, 1);
+            assert!(relation.meta.is_dirty);
+            assert_eq!(relation.get(&2).unwrap().unwrap().title, "Third");
+            assert_eq!(relation.get(&1).unwrap().unwrap().title, "First");
+        }
+    }
+
+    mod changeset {
+        use super::*;
+
+        fn sorted(ids: &HashSet<u32>) -> Vec<u32> {
+            let mut ids: Vec<u32> = ids.iter().copied().collect();
+            ids.sort();
+            ids
+        }
+
+        #[test]
+        fn should_track_ids_of_changed_records() {
+            let mut relation = sample_relation();
+            relation
+                .insert(&Post {
+                    id: 4,
+                    title: "Fourth".to_string(),
+                })
+                .unwrap();
+            relation
+                .update(&Post {
+                    id: 1,
+                    title: "Updated".to_string(),
+                })
+                .unwrap();
+            relation.delete(&2).unwrap();
+
+            let changeset = relation.changeset();
+            assert_eq!(sorted(changeset.inserted()), vec![4]);
+            assert_eq!(sorted(changeset.updated()), vec![1]);
+            assert_eq!(sorted(changeset.deleted()), vec![2]);
+        }
+
+        #[test]
+        fn should_accumulate_changes_of_the_same_record() {
+            let mut relation = sample_relation();
+            let fourth = Post {
+                id: 4,
+                title: "Fourth".to_string(),
+            };
+            // Inserted and updated is still inserted
+            relation.insert(&fourth).unwrap();
+            relation.update(&fourth).unwrap();
+            // Inserted and deleted is not a change at all
+            relation
+                .insert(&Post {
+                    id: 5,
+                    ..fourth.clone()
+                })
+                .unwrap();
+            relation.delete(&5).unwrap();
+            // Updated and deleted is deleted
+            relation.update(&first_post()).unwrap();
+            relation.delete(&1).unwrap();
+            // Deleted and inserted again is updated
+            relation.delete(&2).unwrap();
+            relation.insert(&second_post()).unwrap();
+
+            let changeset = relation.changeset();
+            assert_eq!(sorted(changeset.inserted()), vec![4]);
+            assert_eq!(sorted(changeset.updated()), vec![2]);
+            assert_eq!(sorted(changeset.deleted()), vec![1]);
+        }
+
+        #[test]
+        fn should_track_change_of_id_as_delete_and_insert() {
+            let mut relation = sample_relation();
+            relation.update_with(&1, |post| post.id = 10).unwrap();
+
+            let changeset = relation.changeset();
+            assert_eq!(sorted(changeset.inserted()), vec![10]);
+            assert_eq!(sorted(changeset.deleted()), vec![1]);
+        }
+
+        #[test]
+        fn should_be_cleared_on_reset_dirty() {
+            let mut relation = sample_relation();
+            relation.delete(&1).unwrap();
+            relation.reset_dirty();
+            assert!(relation.changeset().is_empty());
+        }
+    }
+
     mod delete {
         use super::*;
 
@@ -427,11 +1160,27 @@ mod tests {
             let deleted_post = relation.delete(&id).unwrap().unwrap();
 
             assert_eq!(relation.records.len(), 2);
-            assert_eq!(relation.records[0], second_post());
-            assert_eq!(relation.meta.is_dirty, true);
+            assert!(relation.records.contains(&second_post()));
+            assert!(relation.records.contains(&third_post()));
+            assert!(relation.meta.is_dirty);
             assert_eq!(deleted_post, first_post());
         }
 
+        #[test]
+        fn should_keep_index_consistent_after_delete() {
+            let mut relation = sample_relation();
+            relation.delete(&1).unwrap();
+
+            // The last record is moved into the position of the deleted one
+            assert_eq!(relation.get(&1).unwrap(), None);
+            assert_eq!(relation.get(&2).unwrap(), Some(second_post()));
+            assert_eq!(relation.get(&3).unwrap(), Some(third_post()));
+
+            relation.delete(&3).unwrap();
+            assert_eq!(relation.get(&2).unwrap(), Some(second_post()));
+            assert_eq!(relation.get(&3).unwrap(), None);
+        }
+
         #[test]
         fn should_return_none_when_record_not_found() {
             let mut relation = sample_relation();
@@ -439,7 +1188,7 @@ mod tests {
             let maybe_post = relation.delete(&id).unwrap();
             assert!(maybe_post.is_none());
             assert_eq!(relation.records.len(), 3);
-            assert_eq!(relation.meta.is_dirty, false);
+            assert!(!relation.meta.is_dirty);
         }
     }
 
@@ -459,7 +1208,11 @@ mod tests {
 
             assert_eq!(relation.records.len(), 1);
             assert_eq!(relation.records[0], first_post());
-            assert_eq!(relation.meta.is_dirty, true);
+            assert!(relation.meta.is_dirty);
+
+            // Index is rebuilt
+            assert_eq!(relation.get(&1).unwrap(), Some(first_post()));
+            assert_eq!(relation.get(&2).unwrap(), None);
         }
 
         #[test]
@@ -472,7 +1225,7 @@ mod tests {
             assert_eq!(deleted_records.len(), 0);
 
             assert_eq!(relation.records.len(), 3);
-            assert_eq!(relation.meta.is_dirty, false);
+            assert!(!relation.meta.is_dirty);
         }
     }
 
@@ -511,15 +1264,267 @@ mod tests {
         }
     }
 
+    mod get_all_by_index {
+        use super::*;
+
+        fn find_by_title(relation: &Relation<Post>, title: &str) -> Vec<Post> {
+            relation
+                .get_all_by_index(TITLE_INDEX, &title.to_string())
+                .unwrap()
+        }
+
+        #[test]
+        fn should_return_records_with_matching_value() {
+            let mut relation = sample_relation();
+            let another_second = Post {
+                id: 22,
+                title: "Second".to_string(),
+            };
+            relation.insert(&another_second).unwrap();
+
+            let mut posts = find_by_title(&relation, "Second");
+            posts.sort_by_key(|post| post.id);
+            assert_eq!(posts, vec![second_post(), another_second]);
+
+            assert!(find_by_title(&relation, "Unknown").is_empty());
+        }
+
+        #[test]
+        fn should_keep_index_up_to_date_on_update() {
+            let mut relation = sample_relation();
+            let renamed_post = Post {
+                id: 2,
+                title: "Renamed".to_string(),
+            };
+            relation.update(&renamed_post).unwrap();
+
+            assert!(find_by_title(&relation, "Second").is_empty());
+            assert_eq!(find_by_title(&relation, "Renamed"), vec![renamed_post]);
+        }
+
+        #[test]
+        fn should_keep_index_up_to_date_on_delete() {
+            let mut relation = sample_relation();
+            relation.delete(&1).unwrap();
+            relation.delete_all_by(|post| post.id == 3).unwrap();
+
+            assert!(find_by_title(&relation, "First").is_empty());
+            assert!(find_by_title(&relation, "Third").is_empty());
+            assert_eq!(find_by_title(&relation, "Second"), vec![second_post()]);
+        }
+
+        #[test]
+        fn should_build_index_on_deserialize() {
+            let json = r#"[{"id":10,"title":"One"},{"id":20,"title":"Two"}]"#;
+            let relation: Relation<Post> = serde_json::from_str(json).unwrap();
+
+            let posts = find_by_title(&relation, "Two");
+            assert_eq!(posts.len(), 1);
+            assert_eq!(posts[0].id, 20);
+        }
+    }
+
+    mod unique {
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct User {
+            id: u32,
+            email: String,
+        }
+
+        impl Model for User {
+            type Id = u32;
+
+            fn id(&self) -> Self::Id {
+                self.id
+            }
+
+            fn model_name() -> &'static str {
+                "User"
+            }
+
+            fn indexes() -> &'static [IndexDefinition<Self>] {
+                const INDEXES: &[IndexDefinition<User>] = &[IndexDefinition {
+                    field: "email",
+                    unique: true,
+                    hasher: |user: &User| IndexDefinition::<User>::hash_value(&user.email),
+                    eq: |a: &User, b: &User| a.email == b.email,
+                    formatter: |user: &User| format!("{:?}", user.email),
+                }];
+                INDEXES
+            }
+        }
+
+        fn user(id: u32, email: &str) -> User {
+            User {
+                id,
+                email: email.to_string(),
+            }
+        }
+
+        fn sample_users() -> Relation<User> {
+            Relation::new_with_records(vec![
+                user(1, "alice@example.com"),
+                user(2, "bob@example.com"),
+            ])
+        }
+
+        fn assert_unique_violation(err: JoydbError) {
+            assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+            assert_eq!(
+                err.to_string(),
+                r#"User with email = "alice@example.com" already exists"#
+            );
+        }
+
+        #[test]
+        fn should_reject_insert_with_taken_value() {
+            let mut relation = sample_users();
+            let err = relation.insert(&user(3, "alice@example.com")).unwrap_err();
+            assert_unique_violation(err);
+
+            assert_eq!(relation.count().unwrap(), 2);
+            assert!(!relation.is_dirty());
+        }
+
+        #[test]
+        fn should_reject_update_with_value_of_another_record() {
+            let mut relation = sample_users();
+            let err = relation.update(&user(2, "alice@example.com")).unwrap_err();
+            assert_unique_violation(err);
+
+            assert_eq!(relation.get(&2).unwrap(), Some(user(2, "bob@example.com")));
+        }
+
+        #[test]
+        fn should_reject_upsert_with_value_of_another_record() {
+            let mut relation = sample_users();
+            let err = relation.upsert(&user(3, "alice@example.com")).unwrap_err();
+            assert_unique_violation(err);
+        }
+
+        #[test]
+        fn should_allow_update_with_own_value() {
+            let mut relation = sample_users();
+            relation.update(&user(1, "alice@example.com")).unwrap();
+            relation.upsert(&user(1, "alice@example.com")).unwrap();
+        }
+
+        #[test]
+        fn should_allow_value_released_by_another_record() {
+            let mut relation = sample_users();
+            relation.update(&user(1, "alice@new.example.com")).unwrap();
+            relation.insert(&user(3, "alice@example.com")).unwrap();
+
+            relation.delete(&2).unwrap();
+            relation.insert(&user(4, "bob@example.com")).unwrap();
+        }
+    }
+
+    mod hooks {
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct Tag {
+            id: u32,
+            name: String,
+            revision: u32,
+        }
+
+        impl ModelHooks for Tag {
+            fn validate(&self) -> Result<(), ValidationError> {
+                if self.name.is_empty() {
+                    return Err("name must not be empty".into());
+                }
+                Ok(())
+            }
+
+            fn before_insert(&mut self) {
+                self.name = self.name.to_lowercase();
+            }
+
+            fn before_update(&mut self, old: &Self) {
+                self.name = self.name.to_lowercase();
+                self.revision = old.revision + 1;
+            }
+        }
+
+        impl Model for Tag {
+            type Id = u32;
+
+            fn id(&self) -> Self::Id {
+                self.id
+            }
+
+            fn model_name() -> &'static str {
+                "Tag"
+            }
+
+            fn hooks() -> Hooks<Self> {
+                Hooks::from_model_hooks()
+            }
+        }
+
+        fn tag(id: u32, name: &str) -> Tag {
+            Tag {
+                id,
+                name: name.to_string(),
+                revision: 0,
+            }
+        }
+
+        #[test]
+        fn should_run_before_insert() {
+            let mut relation = Relation::default();
+            relation.insert(&tag(1, "Rust")).unwrap();
+            relation.upsert(&tag(2, "Serde")).unwrap();
+
+            assert_eq!(relation.record(&1).unwrap().name, "rust");
+            assert_eq!(relation.record(&2).unwrap().name, "serde");
+            assert_eq!(relation.record(&2).unwrap().revision, 0);
+        }
+
+        #[test]
+        fn should_run_before_update() {
+            let mut relation = Relation::new_with_records(vec![tag(1, "rust")]);
+            relation.update(&tag(1, "Rustlang")).unwrap();
+            relation.upsert(&tag(1, "Rust")).unwrap();
+
+            let updated = relation.record(&1).unwrap();
+            assert_eq!(updated.name, "rust");
+            assert_eq!(updated.revision, 2);
+        }
+
+        #[test]
+        fn should_reject_invalid_records() {
+            let mut relation = Relation::new_with_records(vec![tag(1, "rust")]);
+
+            let err = relation.insert(&tag(2, "")).unwrap_err();
+            assert_eq!(err.to_string(), "Invalid Tag: name must not be empty");
+            assert!(matches!(
+                relation.update(&tag(1, "")),
+                Err(JoydbError::Validation { .. })
+            ));
+            assert!(matches!(
+                relation.upsert(&tag(3, "")),
+                Err(JoydbError::Validation { .. })
+            ));
+
+            assert_eq!(relation.records(), [tag(1, "rust")]);
+            assert!(!relation.is_dirty());
+        }
+    }
+
     #[test]
     fn should_reset_dirty() {
         let mut relation = sample_relation();
-        assert_eq!(relation.is_dirty(), false);
+        assert!(!relation.is_dirty());
 
         relation.delete(&1).unwrap();
-        assert_eq!(relation.is_dirty(), true);
+        assert!(relation.is_dirty());
 
         relation.reset_dirty();
-        assert_eq!(relation.is_dirty(), false);
+        assert!(!relation.is_dirty());
     }
 }
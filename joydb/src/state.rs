@@ -1,13 +1,23 @@
 use serde::{Serialize, de::DeserializeOwned};
 use std::fmt::Debug;
 
-use crate::{JoydbError, Model, Relation, adapters::PartitionedAdapter};
+use crate::{IdCounter, JoydbError, Model, Relation, adapters::PartitionedAdapter};
 
 /// A trait that represents a state that can be (de)serialized to/from JSON or any other format
 /// supported by an adapter.
 ///
 /// A state must be defined with the [state!](crate::state) macro.
 pub trait State: Default + Debug + Serialize + DeserializeOwned + Send + 'static {
+    /// Version of the schema, which is the number of migrations declared in the
+    /// [state!](crate::state) macro. See [migration](crate::migration) module.
+    const SCHEMA_VERSION: u32 = 0;
+
+    /// Returns the relation with the last generated ids of the models. See [IdCounter].
+    fn id_counters(&self) -> &Relation<IdCounter>;
+
+    /// Returns the relation with the last generated ids of the models for modification.
+    fn id_counters_mut(&mut self) -> &mut Relation<IdCounter>;
+
     /// Are there any unsaved changes in the state?
     fn is_dirty(&self) -> bool;
 
@@ -18,7 +28,7 @@ pub trait State: Default + Debug + Serialize + DeserializeOwned + Send + 'static
     ///
     /// The method exists to facilitate work of partitioned adapters. Since partitioned adapters
     /// cannot know which relations they need to work with, this method is essentially the bridge,
-    /// that calls [PartitionedAdapter::write_relation] for every dirty relation in the state.
+    /// that calls [PartitionedAdapter::write_relation_changes] for every dirty relation in the state.
     fn write_with_partitioned_adapter<PA: PartitionedAdapter>(
         &self,
         adapter: &PA,
@@ -32,6 +42,32 @@ pub trait State: Default + Debug + Serialize + DeserializeOwned + Send + 'static
     fn load_with_partitioned_adapter<PA: PartitionedAdapter>(
         adapter: &PA,
     ) -> Result<Self, JoydbError>;
+
+    /// Calls the visitor for every relation in the state.
+    ///
+    /// The method exists to allow generic code (e.g. adapters) to work with every relation,
+    /// without knowing the concrete models.
+    fn visit_relations_mut<V: RelationVisitor>(
+        &mut self,
+        visitor: &mut V,
+    ) -> Result<(), JoydbError>;
+
+    /// Calls the inspector for every relation in the state.
+    ///
+    /// Same as [State::visit_relations_mut], but does not require mutable access.
+    fn inspect_relations<I: RelationInspector>(&self, inspector: &mut I) -> Result<(), JoydbError>;
+}
+
+/// A visitor of relations of a state. See [State::visit_relations_mut].
+pub trait RelationVisitor {
+    /// Is called for every relation in the state.
+    fn visit<M: Model>(&mut self, relation: &mut Relation<M>) -> Result<(), JoydbError>;
+}
+
+/// An inspector of relations of a state. See [State::inspect_relations].
+pub trait RelationInspector {
+    /// Is called for every relation in the state.
+    fn inspect<M: Model>(&mut self, relation: &Relation<M>) -> Result<(), JoydbError>;
 }
 
 /// A utility trait that implemented by a state that can store a relation of a model.
@@ -71,35 +107,68 @@ pub trait GetRelation<M: Model> {
 /// ```
 ///
 /// Where `User` and `Post` are models that implement the [Model] trait.
+///
+/// Besides the relations of the models, the state has the `JoydbIdCounter` relation,
+/// where the last generated ids are persisted. See [IdCounter].
+/// Partitioned adapters write it only once an id is generated with [Increment](crate::Increment),
+/// unified adapters always store it next to the relations of the models.
+/// So the name is reserved and a model with the same name does not compile:
+///
+/// ```compile_fail
+/// # use joydb::Model;
+/// # use serde::{Serialize, Deserialize};
+/// #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+/// struct JoydbIdCounter {
+///     id: u32,
+/// }
+///
+/// joydb::state! {
+///    AppState,
+///    models: [JoydbIdCounter],
+/// }
+/// ```
+///
+/// Schema migrations can be declared with `migrations: [User => migrate_user, ...]`.
+/// See [migration](crate::migration) module (requires `migrations` feature).
+/// A state with migrations can be used only with unified adapters, partitioned adapters
+/// refuse to load it with [JoydbError::Deserialize].
 #[macro_export]
 macro_rules! state {
+    // Implements everything except (de)serialization
     (
-        $state_type:ident,
-        models: [$(
-            $model_type:ident
-        ),*] $(,)?
+        @impl $state_type:ident,
+        models: [$($model_type:ident),*],
+        schema_version: $schema_version:expr
     ) => {
-        /// A struct that holds the data and can be (de)serialized to/from JSON.
-        #[derive(Debug, Default, ::serde::Serialize, ::serde::Deserialize)]
-        #[serde(default)]
-        #[allow(non_snake_case)]
-        pub struct $state_type {
-            $(
-                $model_type: ::joydb::Relation<$model_type>
-            ),+
-        }
+        $(
+            const _: () = assert!(
+                !::joydb::is_reserved_model_name(stringify!($model_type)),
+                concat!(stringify!($model_type), " is a reserved model name"),
+            );
+        )*
 
         impl ::joydb::State for $state_type {
+            const SCHEMA_VERSION: u32 = $schema_version;
+
+            fn id_counters(&self) -> &::joydb::Relation<::joydb::IdCounter> {
+                &self.joydb_id_counters
+            }
+
+            fn id_counters_mut(&mut self) -> &mut ::joydb::Relation<::joydb::IdCounter> {
+                &mut self.joydb_id_counters
+            }
+
             fn is_dirty(&self) -> bool {
                 $(
                     self.$model_type.is_dirty()
-                )||+
+                )||+ || self.joydb_id_counters.is_dirty()
             }
 
             fn reset_dirty(&mut self) {
                 $(
                     self.$model_type.reset_dirty();
                 )*
+                self.joydb_id_counters.reset_dirty();
             }
 
             fn write_with_partitioned_adapter<PA: ::joydb::adapters::PartitionedAdapter>(&self, adapter: &PA) -> Result<(), ::joydb::JoydbError> {
@@ -107,10 +176,14 @@ macro_rules! state {
                     {
                         let relation = &self.$model_type;
                         if relation.is_dirty() {
-                            adapter.write_relation(relation)?;
+                            adapter.write_relation_changes(relation, relation.changeset())?;
                         }
                     }
                 )*
+                if self.joydb_id_counters.is_dirty() {
+                    let relation = &self.joydb_id_counters;
+                    adapter.write_relation_changes(relation, relation.changeset())?;
+                }
                 Ok(())
             }
 
@@ -119,8 +192,25 @@ macro_rules! state {
                 $(
                     state.$model_type = adapter.load_relation::<$model_type>()?;
                 )*
+                state.joydb_id_counters = adapter.load_relation::<::joydb::IdCounter>()?;
                 Ok(state)
             }
+
+            fn visit_relations_mut<V: ::joydb::RelationVisitor>(&mut self, visitor: &mut V) -> Result<(), ::joydb::JoydbError> {
+                $(
+                    visitor.visit(&mut self.$model_type)?;
+                )*
+                visitor.visit(&mut self.joydb_id_counters)?;
+                Ok(())
+            }
+
+            fn inspect_relations<I: ::joydb::RelationInspector>(&self, inspector: &mut I) -> Result<(), ::joydb::JoydbError> {
+                $(
+                    inspector.inspect(&self.$model_type)?;
+                )*
+                inspector.inspect(&self.joydb_id_counters)?;
+                Ok(())
+            }
         }
 
         $(
@@ -134,5 +224,118 @@ macro_rules! state {
                 }
             }
         )+
+    };
+
+    (@one $token:tt) => { 1 };
+
+    (
+        $state_type:ident,
+        models: [$(
+            $model_type:ident
+        ),*] $(,)?
+    ) => {
+        /// A struct that holds the data and can be (de)serialized to/from JSON.
+        #[derive(Debug, Default, ::serde::Serialize, ::serde::Deserialize)]
+        #[serde(default)]
+        #[allow(non_snake_case)]
+        pub struct $state_type {
+            $(
+                $model_type: ::joydb::Relation<$model_type>,
+            )+
+            #[serde(rename = "JoydbIdCounter")]
+            joydb_id_counters: ::joydb::Relation<::joydb::IdCounter>,
+        }
+
+        ::joydb::state!(
+            @impl $state_type,
+            models: [$($model_type),*],
+            schema_version: 0
+        );
+    };
+
+    (
+        $state_type:ident,
+        models: [$(
+            $model_type:ident
+        ),*],
+        migrations: [$(
+            $migration_model:ident => $migrate:expr
+        ),* $(,)?] $(,)?
+    ) => {
+        /// A struct that holds the data and can be (de)serialized to/from JSON.
+        #[derive(Debug, Default)]
+        #[allow(non_snake_case)]
+        pub struct $state_type {
+            $(
+                $model_type: ::joydb::Relation<$model_type>,
+            )+
+            joydb_id_counters: ::joydb::Relation<::joydb::IdCounter>,
+        }
+
+        ::joydb::state!(
+            @impl $state_type,
+            models: [$($model_type),*],
+            schema_version: 0 $(+ ::joydb::state!(@one $migration_model))*
+        );
+
+        impl ::serde::Serialize for $state_type {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeStruct;
+
+                let len = 2 $(+ ::joydb::state!(@one $model_type))*;
+                let mut state = serializer.serialize_struct(stringify!($state_type), len)?;
+                state.serialize_field(
+                    ::joydb::migration::SCHEMA_VERSION_FIELD,
+                    &<Self as ::joydb::State>::SCHEMA_VERSION,
+                )?;
+                $(
+                    state.serialize_field(stringify!($model_type), &self.$model_type)?;
+                )*
+                state.serialize_field("JoydbIdCounter", &self.joydb_id_counters)?;
+                state.end()
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $state_type {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                use ::serde::de::Error;
+
+                let migrations = [$(
+                    ::joydb::migration::Migration::new(stringify!($migration_model), $migrate)
+                ),*];
+                let mut migrator = ::joydb::migration::Migrator::deserialize(deserializer, &migrations)?;
+
+                let mut state = Self::default();
+                $(
+                    state.$model_type = migrator
+                        .take_relation(stringify!($model_type))
+                        .map_err(D::Error::custom)?;
+                )*
+                state.joydb_id_counters = migrator
+                    .take_relation("JoydbIdCounter")
+                    .map_err(D::Error::custom)?;
+                migrator.finish(&mut state).map_err(D::Error::custom)?;
+                Ok(state)
+            }
+        }
+    };
+}
+
+/// Whether the name is taken by a relation which every state has (e.g. `JoydbIdCounter`),
+/// so it can not be used by a model. It's invoked by the code generated with [state!](crate::state).
+#[doc(hidden)]
+pub const fn is_reserved_model_name(name: &str) -> bool {
+    let name = name.as_bytes();
+    let reserved = b"JoydbIdCounter";
+    if name.len() != reserved.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < name.len() {
+        if name[i] != reserved[i] {
+            return false;
+        }
+        i += 1;
     }
+    true
 }
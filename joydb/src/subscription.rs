@@ -0,0 +1,70 @@
+use crate::{Change, Model};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Senders of the changes to the subscribers, grouped by model name.
+/// See [Joydb::subscribe](crate::Joydb::subscribe).
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    /// Every value is `Vec<Sender<Change<M>>>` of the model with the given name.
+    senders: HashMap<&'static str, Box<dyn Any + Send>>,
+}
+
+impl Subscribers {
+    /// Adds a new subscriber to the changes of the model.
+    pub(crate) fn subscribe<M: Model + Send>(&mut self) -> Receiver<Change<M>> {
+        let (sender, receiver) = mpsc::channel();
+        let senders = self
+            .senders
+            .entry(M::model_name())
+            .or_insert_with(|| Box::new(Vec::<Sender<Change<M>>>::new()));
+        downcast_senders_mut(senders).push(sender);
+        receiver
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    pub(crate) fn is_subscribed<M: Model>(&self) -> bool {
+        self.senders.contains_key(M::model_name())
+    }
+
+    /// Sends the changes to every subscriber of the model.
+    /// The subscribers, which have dropped their receivers, are removed.
+    ///
+    /// Returns `false` if the model has no subscribers anymore.
+    pub(crate) fn notify<M: Model>(&mut self, changes: &[Change<M>]) -> bool {
+        let Some(senders) = self.senders.get_mut(M::model_name()) else {
+            return false;
+        };
+        let senders = downcast_senders_mut::<M>(senders);
+        senders.retain(|sender| {
+            changes
+                .iter()
+                .all(|change| sender.send(change.clone()).is_ok())
+        });
+
+        if senders.is_empty() {
+            self.senders.remove(M::model_name());
+            return false;
+        }
+        true
+    }
+}
+
+impl fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.senders.keys()).finish()
+    }
+}
+
+fn downcast_senders_mut<M: Model>(
+    senders: &mut Box<dyn Any + Send>,
+) -> &mut Vec<Sender<Change<M>>> {
+    senders
+        .downcast_mut()
+        .expect("Senders must match the model they are registered for")
+}
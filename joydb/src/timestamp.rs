@@ -0,0 +1,67 @@
+use std::fmt::Debug;
+use std::time::SystemTime;
+
+/// Source of the current time for the fields marked with `#[joydb(created_at)]` and
+/// `#[joydb(updated_at)]`.
+///
+/// The default one is [SystemClock]. A different clock can be set with
+/// [JoydbConfig::clock](crate::JoydbConfig::clock), e.g. to get predictable timestamps in tests.
+pub trait Clock: Debug + Send + Sync + 'static {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// [Clock] that returns the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Type of a field that can be marked with `#[joydb(created_at)]` or `#[joydb(updated_at)]`.
+///
+/// It's implemented for:
+/// - [SystemTime]
+/// - `chrono::DateTime<Utc>` and `chrono::DateTime<Local>` (requires `chrono` feature)
+/// - `time::OffsetDateTime` (requires `time` feature)
+/// - `Option<T>` of any of the above
+pub trait Timestamp {
+    /// Converts the current time obtained from a [Clock].
+    fn from_system_time(time: SystemTime) -> Self;
+}
+
+impl Timestamp for SystemTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        time
+    }
+}
+
+impl<T: Timestamp> Timestamp for Option<T> {
+    fn from_system_time(time: SystemTime) -> Self {
+        Some(T::from_system_time(time))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Local> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+}
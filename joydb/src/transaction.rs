@@ -0,0 +1,269 @@
+use crate::adapters::Adapter;
+use crate::auto_id;
+use crate::db::InnerJoydb;
+use crate::state::{GetRelation, State};
+use crate::{AutoId, Index, JoydbError, Model, Relation};
+use std::hash::Hash;
+
+/// A function that restores a relation to the state it had before the transaction.
+type Rollback<S> = Box<dyn FnOnce(&mut S)>;
+
+/// A transaction created by [`Joydb::transaction`](crate::Joydb::transaction).
+///
+/// It exposes the same CRUD operations as [Joydb](crate::Joydb), but the changes are not
+/// flushed until the transaction is committed.
+/// If the transaction is not committed (e.g. the closure returned an error or panicked),
+/// all the modified relations are restored on drop.
+pub struct Transaction<'a, S: State, A: Adapter> {
+    inner: &'a mut InnerJoydb<S, A>,
+
+    /// Snapshots of the relations modified within the transaction, identified by model name.
+    /// A relation is copied only once, right before its first modification.
+    rollbacks: Vec<(&'static str, Rollback<S>)>,
+}
+
+impl<'a, S: State, A: Adapter> Transaction<'a, S, A> {
+    pub(crate) fn new(inner: &'a mut InnerJoydb<S, A>) -> Self {
+        Self {
+            inner,
+            rollbacks: Vec::new(),
+        }
+    }
+
+    /// Inserts a new record.
+    ///
+    /// # Errors
+    /// Returns an error if the record with the same id already exists.
+    pub fn insert<M: Model>(&mut self, model: &M) -> Result<(), JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.relation_mut::<M>()?.insert(model)
+    }
+
+    /// Inserts a new record with a generated id and returns the id.
+    /// See [Joydb::insert_new](crate::Joydb::insert_new).
+    pub fn insert_new<M: AutoId>(&mut self, model: &M) -> Result<M::Id, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        // Both the relation and the id counters are modified
+        self.relation_mut::<M>()?;
+        self.save_snapshot(S::id_counters_mut);
+        auto_id::insert_new(self.inner.state_mut(), model)
+    }
+
+    /// Finds a record by its id.
+    /// Returns `None` if the record is not found.
+    pub fn get<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.get(id)
+    }
+
+    /// Returns all records that corresponds to the model type.
+    pub fn get_all<M: Model>(&self) -> Result<Vec<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.get_all()
+    }
+
+    /// Return all records that match the predicate.
+    pub fn get_all_by<M, F>(&self, predicate: F) -> Result<Vec<M>, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        self.inner.get_all_by(predicate)
+    }
+
+    /// Returns all records which have the indexed field equal to the given value.
+    pub fn get_all_by_index<M, V>(
+        &self,
+        index: Index<M, V>,
+        value: &V,
+    ) -> Result<Vec<M>, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        V: PartialEq + Hash,
+    {
+        self.inner.get_all_by_index(index, value)
+    }
+
+    /// Returns the number of records that corresponds to the model type.
+    pub fn count<M: Model>(&self) -> Result<usize, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.count()
+    }
+
+    /// Returns the number of records that match the predicate.
+    pub fn count_by<M, F>(&self, predicate: F) -> Result<usize, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        self.inner.count_by(predicate)
+    }
+
+    /// Checks if there is at least one record that matches the predicate.
+    pub fn exists_by<M, F>(&self, predicate: F) -> Result<bool, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        self.inner.exists_by(predicate)
+    }
+
+    /// Gives the closure read access to all the records of the model without cloning them.
+    /// See [Joydb::with_relation](crate::Joydb::with_relation).
+    pub fn with_relation<M, R, F>(&self, f: F) -> Result<R, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: FnOnce(&[M]) -> R,
+    {
+        self.inner.with_relation(f)
+    }
+
+    pub fn update<M: Model>(&mut self, new_record: &M) -> Result<(), JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.relation_mut::<M>()?.update(new_record)
+    }
+
+    /// Modifies the record with the given id in place.
+    /// See [Joydb::update_with](crate::Joydb::update_with).
+    pub fn update_with<M, F>(&mut self, id: &M::Id, mutator: F) -> Result<(), JoydbError>
+    where
+        M: Model + PartialEq,
+        S: GetRelation<M>,
+        F: FnOnce(&mut M),
+    {
+        self.relation_mut::<M>()?.update_with(id, mutator)?;
+        Ok(())
+    }
+
+    /// Modifies all the records that match the predicate in place.
+    /// Returns the number of records that were actually changed.
+    pub fn update_all_by<M, P, F>(&mut self, predicate: P, mutator: F) -> Result<usize, JoydbError>
+    where
+        M: Model + PartialEq,
+        S: GetRelation<M>,
+        P: Fn(&M) -> bool,
+        F: Fn(&mut M),
+    {
+        self.relation_mut::<M>()?.update_all_by(predicate, mutator)
+    }
+
+    /// Upserts a record.
+    /// If the record with the same id already exists, it will be updated.
+    /// Otherwise, it will be inserted.
+    pub fn upsert<M: Model>(&mut self, record: &M) -> Result<(), JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.relation_mut::<M>()?.upsert(record)
+    }
+
+    /// Deletes a record by its id and returns the deleted record.
+    /// If the record is not found, it returns `None`.
+    pub fn delete<M: Model>(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.relation_mut::<M>()?.delete(id)
+    }
+
+    /// Deletes all records that match the predicate.
+    /// Returns the deleted records.
+    pub fn delete_all_by<M, F>(&mut self, predicate: F) -> Result<Vec<M>, JoydbError>
+    where
+        M: Model,
+        S: GetRelation<M>,
+        F: Fn(&M) -> bool,
+    {
+        self.relation_mut::<M>()?.delete_all_by(predicate)
+    }
+
+    /// Finds a record by its id, including a soft deleted one.
+    /// See [Joydb::get_with_deleted](crate::Joydb::get_with_deleted).
+    pub fn get_with_deleted<M: Model>(&self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.inner.get_with_deleted(id)
+    }
+
+    /// Restores a soft deleted record and returns it.
+    /// See [Joydb::restore](crate::Joydb::restore).
+    pub fn restore<M: Model>(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.relation_mut::<M>()?.restore(id)
+    }
+
+    /// Physically removes a record by its id, even if it's soft deleted.
+    /// See [Joydb::purge](crate::Joydb::purge).
+    pub fn purge<M: Model>(&mut self, id: &M::Id) -> Result<Option<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        self.relation_mut::<M>()?.purge(id)
+    }
+
+    /// Applies the changes: flushes the state if it's required by the sync policy.
+    /// If the flush fails, the changes are rolled back and the subscribers are not notified.
+    pub(crate) fn commit(mut self) -> Result<(), JoydbError> {
+        if let Err(err) = self.inner.after_change() {
+            // The adapter might have received the changes, which are about to be rolled back
+            self.inner.discard_changes();
+            return Err(err);
+        }
+        self.rollbacks.clear();
+        Ok(())
+    }
+
+    /// Returns a relation for modification.
+    /// Makes a snapshot of the relation, if it's the first modification within the transaction.
+    fn relation_mut<M: Model>(&mut self) -> Result<&mut Relation<M>, JoydbError>
+    where
+        S: GetRelation<M>,
+    {
+        // Fail before taking a snapshot, if the relation can not be modified
+        self.inner.get_relation_mut::<M>()?;
+
+        self.save_snapshot(<S as GetRelation<M>>::get_relation_mut);
+        self.inner.get_relation_mut::<M>()
+    }
+
+    /// Makes a snapshot of the relation, unless it's already made within the transaction.
+    fn save_snapshot<M: Model>(&mut self, relation: fn(&mut S) -> &mut Relation<M>) {
+        let model_name = M::model_name();
+        let is_saved = self.rollbacks.iter().any(|(name, _)| *name == model_name);
+        if !is_saved {
+            let snapshot = relation(self.inner.state_mut()).clone();
+            let rollback = move |state: &mut S| {
+                *relation(state) = snapshot;
+            };
+            self.rollbacks.push((model_name, Box::new(rollback)));
+        }
+    }
+}
+
+impl<S: State, A: Adapter> Drop for Transaction<'_, S, A> {
+    fn drop(&mut self) {
+        for (_, rollback) in self.rollbacks.drain(..) {
+            rollback(self.inner.state_mut());
+        }
+    }
+}
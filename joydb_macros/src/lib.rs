@@ -8,7 +8,7 @@ use proc_macro::TokenStream;
 
 mod model;
 
-#[proc_macro_derive(Model)]
+#[proc_macro_derive(Model, attributes(joydb))]
 pub fn derive_model(input: TokenStream) -> TokenStream {
     crate::model::derive_model(input)
 }
@@ -1,6 +1,6 @@
-use proc_macro2::{Ident, TokenStream};
-use quote::quote;
-use syn::{Data, DeriveInput, Field, Fields};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Field, Fields, Visibility};
 
 pub fn derive_model(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     expand_to_derive_model(input)
@@ -16,12 +16,88 @@ fn expand_to_derive_model(input: proc_macro::TokenStream) -> Result<TokenStream,
 
 struct Model {
     type_name: Ident,
-    id_field: Field,
+    visibility: Visibility,
+    /// Fields of the primary key. If there are multiple fields, the primary key is composite.
+    id_fields: Vec<Field>,
+
+    /// Strategy to generate ids, set with `#[joydb(id, auto = "...")]`.
+    auto_id: Option<AutoIdStrategy>,
+
+    /// Fields marked with `#[joydb(index)]` or `#[joydb(unique)]`.
+    index_fields: Vec<IndexField>,
+
+    /// Whether `ModelHooks` are implemented manually, set with `#[joydb(hooks)]`.
+    has_custom_hooks: bool,
+
+    /// Field marked with `#[joydb(created_at)]`.
+    created_at_field: Option<Field>,
+
+    /// Field marked with `#[joydb(updated_at)]`.
+    updated_at_field: Option<Field>,
+
+    /// `bool` field marked with `#[joydb(soft_delete)]`.
+    soft_delete_field: Option<Field>,
+}
+
+struct IndexField {
+    field: Field,
+    unique: bool,
+}
+
+/// Strategies to generate ids, which can be set with `#[joydb(id, auto = "...")]`.
+enum AutoIdStrategy {
+    Increment,
+    UuidV4,
+    UuidV7,
+}
+
+impl AutoIdStrategy {
+    fn parse(value: &syn::LitStr) -> Result<Self, syn::Error> {
+        match value.value().as_str() {
+            "increment" => Ok(Self::Increment),
+            "uuid_v4" => Ok(Self::UuidV4),
+            "uuid_v7" => Ok(Self::UuidV7),
+            _ => Err(syn::Error::new(
+                value.span(),
+                "Unknown id generation strategy. Expected one of: `increment`, `uuid_v4`, `uuid_v7`",
+            )),
+        }
+    }
+
+    /// Path to the type implementing `IdGenerator`.
+    fn generator(&self) -> TokenStream {
+        match self {
+            Self::Increment => quote!(::joydb::Increment),
+            Self::UuidV4 => quote!(::joydb::UuidV4),
+            Self::UuidV7 => quote!(::joydb::UuidV7),
+        }
+    }
 }
 
-// At the moment id field is hardcoded to be `id`, but could be changed in the future to be
-// parameterized if necessary.
-const ID_NAME: &str = "id";
+/// Options set with `#[joydb(...)]` attribute on a struct.
+#[derive(Default)]
+struct StructAttributes {
+    /// Name of the primary key field, set with `#[joydb(id = "...")]`.
+    id: Option<syn::LitStr>,
+
+    /// Whether `ModelHooks` are implemented manually, set with `#[joydb(hooks)]`.
+    hooks: bool,
+}
+
+/// Options set with `#[joydb(...)]` attribute on a field.
+#[derive(Default)]
+struct FieldAttributes {
+    id: bool,
+    auto: Option<AutoIdStrategy>,
+    index: bool,
+    unique: bool,
+    created_at: bool,
+    updated_at: bool,
+    soft_delete: bool,
+}
+
+/// Name of the primary key field, unless it's specified explicitly.
+const DEFAULT_ID_NAME: &str = "id";
 
 fn parse_model_struct(input: &DeriveInput) -> Result<Model, syn::Error> {
     const BAD_TYPE_ERROR_MSG: &str = "Model must be a struct with named fields";
@@ -33,43 +109,436 @@ fn parse_model_struct(input: &DeriveInput) -> Result<Model, syn::Error> {
         return Err(syn::Error::new(input.ident.span(), BAD_TYPE_ERROR_MSG));
     };
 
-    let id_field = fields
-        .named
-        .iter()
-        .find(|field| field.ident.as_ref().is_some_and(|ident| ident == ID_NAME))
-        .ok_or_else(|| syn::Error::new(input.ident.span(), "Model must have an `id` field"))?;
+    let struct_attributes = parse_struct_attributes(input)?;
+
+    let mut marked_id_fields: Vec<Field> = Vec::new();
+    let mut index_fields = Vec::new();
+    let mut auto_id = None;
+    let mut created_at_field = None;
+    let mut updated_at_field = None;
+    let mut soft_delete_field = None;
+    for field in &fields.named {
+        let attributes = parse_field_attributes(field)?;
+        if attributes.created_at {
+            set_marker_field(&mut created_at_field, field, &attributes, "created_at")?;
+        }
+        if attributes.updated_at {
+            set_marker_field(&mut updated_at_field, field, &attributes, "updated_at")?;
+        }
+        if attributes.soft_delete {
+            set_marker_field(&mut soft_delete_field, field, &attributes, "soft_delete")?;
+        }
+        if let Some(strategy) = attributes.auto {
+            if !attributes.id {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`auto` can be used only together with `id`: `#[joydb(id, auto = \"...\")]`",
+                ));
+            }
+            auto_id = Some(strategy);
+        }
+        if attributes.id {
+            if struct_attributes.id.is_some() {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "Primary key is ambiguous: it's set with both `#[joydb(id = \"...\")]` on the struct and `#[joydb(id)]` on the field",
+                ));
+            }
+            marked_id_fields.push(field.clone());
+        }
+        // Unique constraint is backed by an index, so unique fields are always indexed
+        if attributes.index || attributes.unique {
+            index_fields.push(IndexField {
+                field: field.clone(),
+                unique: attributes.unique,
+            });
+        }
+    }
+
+    let id_fields = if !marked_id_fields.is_empty() {
+        marked_id_fields
+    } else if let Some(id_name) = &struct_attributes.id {
+        let field = find_field(fields, &id_name.value()).ok_or_else(|| {
+            syn::Error::new(
+                id_name.span(),
+                format!("Model has no field named `{}`", id_name.value()),
+            )
+        })?;
+        vec![field.clone()]
+    } else {
+        let field = find_field(fields, DEFAULT_ID_NAME).ok_or_else(|| {
+            syn::Error::new(
+                input.ident.span(),
+                "Model must have an `id` field or a field marked with `#[joydb(id)]`",
+            )
+        })?;
+        vec![field.clone()]
+    };
+
+    if auto_id.is_some() && id_fields.len() > 1 {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "Ids can not be generated for a composite primary key",
+        ));
+    }
 
     Ok(Model {
         type_name: input.ident.clone(),
-        id_field: id_field.clone(),
+        visibility: input.vis.clone(),
+        id_fields,
+        auto_id,
+        index_fields,
+        has_custom_hooks: struct_attributes.hooks,
+        created_at_field,
+        updated_at_field,
+        soft_delete_field,
     })
 }
 
+/// Remembers the field marked with an attribute like `#[joydb(created_at)]`
+/// making sure there is only one such field.
+fn set_marker_field(
+    marker_field: &mut Option<Field>,
+    field: &Field,
+    attributes: &FieldAttributes,
+    attribute_name: &str,
+) -> Result<(), syn::Error> {
+    if attributes.id {
+        return Err(syn::Error::new_spanned(
+            field,
+            format!("`{attribute_name}` can not be used together with `id`"),
+        ));
+    }
+    if marker_field.is_some() {
+        return Err(syn::Error::new_spanned(
+            field,
+            format!("Only one field can be marked with `#[joydb({attribute_name})]`"),
+        ));
+    }
+    *marker_field = Some(field.clone());
+    Ok(())
+}
+
+fn find_field<'a>(fields: &'a syn::FieldsNamed, name: &str) -> Option<&'a Field> {
+    fields
+        .named
+        .iter()
+        .find(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+}
+
+fn parse_struct_attributes(input: &DeriveInput) -> Result<StructAttributes, syn::Error> {
+    let mut attributes = StructAttributes::default();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("joydb")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                attributes.id = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("hooks") {
+                attributes.hooks = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown joydb attribute. Expected one of: `id = \"...\"`, `hooks`"))
+            }
+        })?;
+    }
+    Ok(attributes)
+}
+
+fn parse_field_attributes(field: &Field) -> Result<FieldAttributes, syn::Error> {
+    let mut attributes = FieldAttributes::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("joydb")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                attributes.id = true;
+                Ok(())
+            } else if meta.path.is_ident("auto") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.auto = Some(AutoIdStrategy::parse(&value)?);
+                Ok(())
+            } else if meta.path.is_ident("index") {
+                attributes.index = true;
+                Ok(())
+            } else if meta.path.is_ident("unique") {
+                attributes.unique = true;
+                Ok(())
+            } else if meta.path.is_ident("created_at") {
+                attributes.created_at = true;
+                Ok(())
+            } else if meta.path.is_ident("updated_at") {
+                attributes.updated_at = true;
+                Ok(())
+            } else if meta.path.is_ident("soft_delete") {
+                attributes.soft_delete = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown joydb attribute. Expected one of: `id`, `auto = \"...\"`, `index`, `unique`, `created_at`, `updated_at`, `soft_delete`"))
+            }
+        })?;
+    }
+    Ok(attributes)
+}
+
 fn gen_derive_model(model: &Model) -> TokenStream {
     let Model {
         type_name,
-        id_field,
+        id_fields,
+        index_fields,
+        ..
     } = model;
 
-    let id_field_type = &id_field.ty;
-    let id_field_name = id_field
-        .ident
-        .as_ref()
-        .expect("id field name is guaranteed to be present");
+    let (id_type, id_expr) = gen_id_type_and_expr(id_fields);
 
     let type_name_str: String = type_name.to_string();
 
+    let indexes_fn = gen_indexes_fn(model);
+    let set_created_at_fn = gen_set_timestamp_fn(&model.created_at_field, "set_created_at");
+    let set_updated_at_fn = gen_set_timestamp_fn(&model.updated_at_field, "set_updated_at");
+    let copy_created_at_fn = gen_copy_created_at_fn(&model.created_at_field);
+    let soft_delete_fns = gen_soft_delete_fns(model);
+    let index_type = if index_fields.is_empty() {
+        quote!()
+    } else {
+        gen_index_type(model)
+    };
+    let auto_id_impl = gen_auto_id_impl(model);
+    let hooks_impl = if model.has_custom_hooks {
+        quote!()
+    } else {
+        quote!(impl ::joydb::ModelHooks for #type_name {})
+    };
+
     quote! {
         impl ::joydb::Model for #type_name {        // impl ::joydb::Model for User {
-            type Id = #id_field_type;               //     type Id = i32
+            type Id = #id_type;                     //     type Id = i32
                                                     //
-            fn id(&self) -> &Self::Id {             //     fn id(&self) -> Self::Id {
-                &self.#id_field_name                //         &self.id
+            fn id(&self) -> Self::Id {              //     fn id(&self) -> Self::Id {
+                #id_expr                            //         self.id.clone()
             }                                       //     }
                                                     //
             fn model_name() -> &'static str {       //     fn model_name() -> &'static str {
                 #type_name_str                      //         "User"
             }                                       //     }
-        }                                           // }
+
+            #indexes_fn
+
+            fn serialize_id<S: ::serde::Serializer>(
+                id: &Self::Id,
+                serializer: S,
+            ) -> ::std::result::Result<S::Ok, S::Error> {
+                ::serde::Serialize::serialize(id, serializer)
+            }
+
+            fn hooks() -> ::joydb::Hooks<Self> {
+                ::joydb::Hooks::from_model_hooks()
+            }
+
+            #set_created_at_fn
+
+            #copy_created_at_fn
+
+            #set_updated_at_fn
+
+            #soft_delete_fns
+        }
+
+        #index_type
+
+        #auto_id_impl
+
+        #hooks_impl
+    }
+}
+
+/// Generates `AutoId` implementation if the id field is marked with `#[joydb(id, auto = "...")]`.
+fn gen_auto_id_impl(model: &Model) -> TokenStream {
+    let Some(strategy) = &model.auto_id else {
+        return quote!();
+    };
+    let [id_field] = model.id_fields.as_slice() else {
+        unreachable!("Auto id is allowed only for a single id field");
+    };
+    let type_name = &model.type_name;
+    let id_name = field_name(id_field);
+    let generator = strategy.generator();
+
+    quote! {
+        impl ::joydb::AutoId for #type_name {
+            type Generator = #generator;
+
+            fn set_id(&mut self, id: Self::Id) {
+                self.#id_name = id;
+            }
+        }
+    }
+}
+
+/// Generates `Model::set_created_at()` or `Model::set_updated_at()` function,
+/// if the corresponding field is present.
+fn gen_set_timestamp_fn(timestamp_field: &Option<Field>, fn_name: &str) -> TokenStream {
+    let Some(field) = timestamp_field else {
+        return quote!();
+    };
+    let name = field_name(field);
+    let fn_name = Ident::new(fn_name, Span::call_site());
+    quote! {
+        fn #fn_name(&mut self, now: ::std::time::SystemTime) {
+            self.#name = ::joydb::Timestamp::from_system_time(now);
+        }
     }
 }
+
+/// Generates `Model::copy_created_at()` function, if a field is marked with `#[joydb(created_at)]`.
+fn gen_copy_created_at_fn(created_at_field: &Option<Field>) -> TokenStream {
+    let Some(field) = created_at_field else {
+        return quote!();
+    };
+    let name = field_name(field);
+    quote! {
+        fn copy_created_at(&mut self, from: &Self) {
+            self.#name = ::std::clone::Clone::clone(&from.#name);
+        }
+    }
+}
+
+/// Generates the functions of `Model` related to soft delete,
+/// if a field is marked with `#[joydb(soft_delete)]`.
+fn gen_soft_delete_fns(model: &Model) -> TokenStream {
+    let Some(field) = &model.soft_delete_field else {
+        return quote!();
+    };
+    let name = field_name(field);
+    quote! {
+        fn has_soft_delete() -> bool {
+            true
+        }
+
+        fn is_deleted(&self) -> bool {
+            self.#name
+        }
+
+        fn set_deleted(&mut self, deleted: bool) {
+            self.#name = deleted;
+        }
+    }
+}
+
+/// Generates the type of the primary key and the expression to obtain it from `self`.
+/// For a composite primary key, both are tuples, e.g. `(UserId, GroupId)` and
+/// `(self.user_id.clone(), self.group_id.clone())`.
+fn gen_id_type_and_expr(id_fields: &[Field]) -> (TokenStream, TokenStream) {
+    if let [field] = id_fields {
+        let id_type = &field.ty;
+        let name = field_name(field);
+        (quote!(#id_type), quote!(self.#name.clone()))
+    } else {
+        let types = id_fields.iter().map(|field| &field.ty);
+        let names = id_fields.iter().map(field_name);
+        (
+            quote!( (#(#types),*) ),
+            quote!( (#(self.#names.clone()),*) ),
+        )
+    }
+}
+
+/// Generates `Model::indexes()` function.
+fn gen_indexes_fn(model: &Model) -> TokenStream {
+    if model.index_fields.is_empty() {
+        return quote!();
+    }
+
+    let type_name = &model.type_name;
+    let definitions = model
+        .index_fields
+        .iter()
+        .map(|IndexField { field, unique }| {
+            let name = field_name(field);
+            let name_str = name.to_string();
+            quote! {
+                ::joydb::IndexDefinition {
+                    field: #name_str,
+                    unique: #unique,
+                    hasher: |record: &#type_name| {
+                        ::joydb::IndexDefinition::<#type_name>::hash_value(&record.#name)
+                    },
+                    eq: |a: &#type_name, b: &#type_name| a.#name == b.#name,
+                    formatter: |record: &#type_name| format!("{:?}", record.#name),
+                }
+            }
+        });
+
+    quote! {
+        fn indexes() -> &'static [::joydb::IndexDefinition<Self>] {
+            const INDEXES: &[::joydb::IndexDefinition<#type_name>] = &[#(#definitions),*];
+            INDEXES
+        }
+    }
+}
+
+/// Generates `<Model>Index` type, which holds typed references to the indexes as associated
+/// constants. E.g. for `Post` model with indexed `user_id` field it generates:
+///
+/// ```text
+/// pub struct PostIndex;
+///
+/// impl PostIndex {
+///     pub const UserId: ::joydb::Index<Post, Uuid> = ::joydb::Index::new(0, |record| &record.user_id);
+/// }
+/// ```
+fn gen_index_type(model: &Model) -> TokenStream {
+    let Model {
+        type_name,
+        visibility,
+        index_fields,
+        ..
+    } = model;
+
+    let index_type_name = format_ident!("{}Index", type_name);
+    let index_type_doc = format!("Secondary indexes of [`{type_name}`].");
+
+    let constants = index_fields
+        .iter()
+        .enumerate()
+        .map(|(position, IndexField { field, .. })| {
+            let name = field_name(field);
+            let field_type = &field.ty;
+            let const_name = Ident::new(&to_pascal_case(&name.to_string()), Span::call_site());
+            let const_doc = format!("Index on `{type_name}::{name}` field.");
+            quote! {
+                #[doc = #const_doc]
+                #[allow(non_upper_case_globals)]
+                #visibility const #const_name: ::joydb::Index<#type_name, #field_type> =
+                    ::joydb::Index::new(#position, |record: &#type_name| &record.#name);
+            }
+        });
+
+    quote! {
+        #[doc = #index_type_doc]
+        #visibility struct #index_type_name;
+
+        impl #index_type_name {
+            #(#constants)*
+        }
+    }
+}
+
+fn field_name(field: &Field) -> &Ident {
+    field
+        .ident
+        .as_ref()
+        .expect("Model is guaranteed to have only named fields")
+}
+
+/// Converts `snake_case` field name into `PascalCase`, e.g. `user_id` -> `UserId`.
+fn to_pascal_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -6,6 +6,7 @@ use uuid::Uuid;
 pub struct Post {
     pub id: Uuid,
     pub content: String,
+    #[joydb(index)]
     pub user_id: Uuid,
 }
 
@@ -16,9 +17,35 @@ pub struct User {
     pub age: u8,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+pub struct Tag {
+    #[joydb(id, auto = "uuid_v4")]
+    pub id: Uuid,
+    #[joydb(unique)]
+    pub name: String,
+}
+
+/// A join model with a composite primary key.
+#[derive(Debug, Clone, Serialize, Deserialize, Model)]
+pub struct PostTag {
+    #[joydb(id)]
+    pub post_id: Uuid,
+    #[joydb(id)]
+    pub tag_id: Uuid,
+}
+
+/// A model with sequential ids generated by the database.
+#[derive(Debug, Clone, Serialize, Deserialize, Model)]
+pub struct Comment {
+    #[joydb(id, auto = "increment")]
+    pub id: u32,
+    pub post_id: Uuid,
+    pub text: String,
+}
+
 state! {
     DatabaseState,
-    models: [User, Post],
+    models: [User, Post, Tag, PostTag, Comment],
 }
 
 pub type Database = Joydb<DatabaseState, JsonAdapter>;
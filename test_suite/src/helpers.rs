@@ -15,13 +15,60 @@ pub fn gen_db_file_path() -> String {
 pub fn with_open_db<F>(f: F)
 where
     F: FnOnce(Database),
+{
+    with_db_file_path(|file_path| {
+        let db = Database::open(file_path).unwrap();
+        f(db);
+    });
+}
+
+/// Pass a unique database file path to a closure, so the database can be opened multiple times.
+/// The helper takes care of removing the database file after the closure is executed.
+pub fn with_db_file_path<F>(f: F)
+where
+    F: FnOnce(&str),
 {
     if !std::path::Path::new(DATA_DIR).exists() {
         std::fs::create_dir_all(DATA_DIR).unwrap();
     }
 
     let file_path = gen_db_file_path();
-    let db = Database::open(&file_path).unwrap();
-    f(db);
-    std::fs::remove_file(file_path).unwrap();
+    f(&file_path);
+    std::fs::remove_file(&file_path).unwrap();
+    std::fs::remove_file(format!("{file_path}.lock")).unwrap();
+}
+
+/// Pass a unique database directory path to a closure, so a partitioned database can be opened
+/// multiple times.
+/// The helper takes care of removing the directory after the closure is executed.
+pub fn with_db_dir_path<F>(f: F)
+where
+    F: FnOnce(&str),
+{
+    let dir_path = format!("{DATA_DIR}/test-{}", Uuid::new_v4());
+    f(&dir_path);
+    std::fs::remove_dir_all(&dir_path).unwrap();
+    std::fs::remove_file(format!("{dir_path}.lock")).unwrap();
+}
+
+/// Pass a unique database path to a closure, so the database can be opened multiple times.
+/// Unlike [with_db_file_path] and [with_db_dir_path], the path may end up being either a file or
+/// a directory, which allows to run the same code against unified and partitioned adapters.
+/// The helper takes care of removing the file or the directory after the closure is executed.
+pub fn with_db_path<F>(f: F)
+where
+    F: FnOnce(&str),
+{
+    if !std::path::Path::new(DATA_DIR).exists() {
+        std::fs::create_dir_all(DATA_DIR).unwrap();
+    }
+
+    let path = format!("{DATA_DIR}/test-{}", Uuid::new_v4());
+    f(&path);
+    if std::path::Path::new(&path).is_dir() {
+        std::fs::remove_dir_all(&path).unwrap();
+    } else {
+        std::fs::remove_file(&path).unwrap();
+    }
+    std::fs::remove_file(format!("{path}.lock")).unwrap();
 }
@@ -0,0 +1,197 @@
+//! Scenarios that every adapter is expected to pass.
+//!
+//! Every scenario opens the database at least twice, so it checks not only the in-memory
+//! behavior, but also that the data survive the round trip through the adapter.
+//! Use [adapter_scenarios!](crate::adapter_scenarios) to generate the tests for an adapter.
+
+use joydb::adapters::{Adapter, FromPath};
+use joydb::{Joydb, JoydbError};
+use uuid::Uuid;
+
+use crate::database::{Comment, DatabaseState, Post, PostIndex, PostTag, Tag, User};
+use crate::helpers::with_db_path;
+
+type Db<A> = Joydb<DatabaseState, A>;
+
+/// Generates a test for every scenario, running it against the given adapter.
+///
+/// ```ignore
+/// mod unified {
+///     test_suite::adapter_scenarios!(joydb::adapters::JsonAdapter);
+/// }
+/// ```
+#[macro_export]
+macro_rules! adapter_scenarios {
+    ($adapter:ty) => {
+        $crate::adapter_scenarios!(
+            @tests $adapter,
+            [
+                should_start_with_empty_state,
+                should_persist_inserted_records,
+                should_persist_updates_and_deletes,
+                should_keep_indexes_and_unique_constraints_after_reopening,
+                should_persist_records_with_composite_id,
+                should_continue_id_counter_after_reopening,
+            ]
+        );
+    };
+
+    (@tests $adapter:ty, [$($scenario:ident),* $(,)?]) => {
+        $(
+            #[test]
+            fn $scenario() {
+                $crate::scenarios::$scenario::<$adapter>();
+            }
+        )*
+    };
+}
+
+fn user(name: &str, age: u8) -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        age,
+    }
+}
+
+pub fn should_start_with_empty_state<A: Adapter + FromPath>() {
+    with_db_path(|path| {
+        {
+            let db = Db::<A>::open(path).unwrap();
+            assert_eq!(db.count::<User>().unwrap(), 0);
+        }
+
+        let db = Db::<A>::open(path).unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 0);
+        assert_eq!(db.count::<Post>().unwrap(), 0);
+    });
+}
+
+pub fn should_persist_inserted_records<A: Adapter + FromPath>() {
+    with_db_path(|path| {
+        let alice = user("Alice", 30);
+        let post = Post {
+            id: Uuid::new_v4(),
+            content: "Hello!".to_string(),
+            user_id: alice.id,
+        };
+        {
+            let db = Db::<A>::open(path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&user("Bob", 25)).unwrap();
+            db.insert(&post).unwrap();
+        }
+
+        let db = Db::<A>::open(path).unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 2);
+
+        let fetched_alice = db.get::<User>(&alice.id).unwrap().unwrap();
+        assert_eq!(fetched_alice.name, "Alice");
+        assert_eq!(fetched_alice.age, 30);
+
+        let fetched_post = db.get::<Post>(&post.id).unwrap().unwrap();
+        assert_eq!(fetched_post.content, "Hello!");
+        assert_eq!(fetched_post.user_id, alice.id);
+    });
+}
+
+pub fn should_persist_updates_and_deletes<A: Adapter + FromPath>() {
+    with_db_path(|path| {
+        let alice = user("Alice", 30);
+        let bob = user("Bob", 25);
+        {
+            let db = Db::<A>::open(path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&bob).unwrap();
+        }
+        {
+            let db = Db::<A>::open(path).unwrap();
+            db.update(&User {
+                name: "Alice Updated".to_string(),
+                ..alice.clone()
+            })
+            .unwrap();
+            db.delete::<User>(&bob.id).unwrap();
+        }
+
+        let db = Db::<A>::open(path).unwrap();
+        let users: Vec<User> = db.get_all().unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, "Alice Updated");
+    });
+}
+
+pub fn should_keep_indexes_and_unique_constraints_after_reopening<A: Adapter + FromPath>() {
+    with_db_path(|path| {
+        let user_id = Uuid::new_v4();
+        {
+            let db = Db::<A>::open(path).unwrap();
+            for content in ["First", "Second"] {
+                db.insert(&Post {
+                    id: Uuid::new_v4(),
+                    content: content.to_string(),
+                    user_id,
+                })
+                .unwrap();
+            }
+            db.insert_new(&Tag {
+                id: Uuid::nil(),
+                name: "rust".to_string(),
+            })
+            .unwrap();
+        }
+
+        let db = Db::<A>::open(path).unwrap();
+        let posts = db
+            .get_all_by_index::<Post, _>(PostIndex::UserId, &user_id)
+            .unwrap();
+        assert_eq!(posts.len(), 2);
+
+        let err = db
+            .insert(&Tag {
+                id: Uuid::new_v4(),
+                name: "rust".to_string(),
+            })
+            .unwrap_err();
+        assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+    });
+}
+
+pub fn should_persist_records_with_composite_id<A: Adapter + FromPath>() {
+    with_db_path(|path| {
+        let post_id = Uuid::new_v4();
+        let tag_id = Uuid::new_v4();
+        {
+            let db = Db::<A>::open(path).unwrap();
+            db.insert(&PostTag { post_id, tag_id }).unwrap();
+        }
+
+        let db = Db::<A>::open(path).unwrap();
+        assert!(db.get::<PostTag>(&(post_id, tag_id)).unwrap().is_some());
+
+        let err = db.insert(&PostTag { post_id, tag_id }).unwrap_err();
+        assert!(matches!(err, JoydbError::DuplicatedId { .. }));
+    });
+}
+
+pub fn should_continue_id_counter_after_reopening<A: Adapter + FromPath>() {
+    let new_comment = |text: &str| Comment {
+        id: 0,
+        post_id: Uuid::new_v4(),
+        text: text.to_string(),
+    };
+
+    with_db_path(|path| {
+        {
+            let db = Db::<A>::open(path).unwrap();
+            db.insert_new(&new_comment("First")).unwrap();
+            db.insert_new(&new_comment("Second")).unwrap();
+            // The id of a deleted record must not be reused
+            db.delete::<Comment>(&2).unwrap();
+        }
+
+        let db = Db::<A>::open(path).unwrap();
+        assert_eq!(db.insert_new(&new_comment("Third")).unwrap(), 3);
+        assert_eq!(db.get::<Comment>(&1).unwrap().unwrap().text, "First");
+    });
+}
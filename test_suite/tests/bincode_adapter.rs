@@ -0,0 +1,54 @@
+use joydb::adapters::BincodeAdapter;
+use joydb::{Joydb, JoydbError};
+use test_suite::database::DatabaseState;
+use test_suite::helpers::with_db_file_path;
+
+type Db = Joydb<DatabaseState, BincodeAdapter>;
+
+test_suite::adapter_scenarios!(BincodeAdapter);
+
+#[test]
+fn should_write_header_in_front_of_state() {
+    with_db_file_path(|file_path| {
+        Db::open(file_path).unwrap();
+
+        let bytes = std::fs::read(file_path).unwrap();
+        assert!(bytes.starts_with(b"JOYDBBIN\x01\x00"));
+    });
+}
+
+#[test]
+fn should_refuse_file_without_header() {
+    with_db_file_path(|file_path| {
+        std::fs::write(file_path, r#"{"User": []}"#).unwrap();
+
+        let Err(err) = Db::open(file_path) else {
+            panic!("expected the file to be refused");
+        };
+        assert!(matches!(err, JoydbError::Deserialize(_)));
+        assert!(
+            err.to_string().contains("not a Joydb bincode file"),
+            "{err}"
+        );
+    });
+}
+
+#[test]
+fn should_refuse_file_with_different_format_version() {
+    with_db_file_path(|file_path| {
+        Db::open(file_path).unwrap();
+        let mut bytes = std::fs::read(file_path).unwrap();
+        bytes[8] = 99;
+        std::fs::write(file_path, &bytes).unwrap();
+
+        let Err(err) = Db::open(file_path) else {
+            panic!("expected the file to be refused");
+        };
+        assert!(matches!(err, JoydbError::Deserialize(_)));
+        assert!(
+            err.to_string()
+                .contains("unsupported bincode format version 99, expected 1"),
+            "{err}"
+        );
+    });
+}
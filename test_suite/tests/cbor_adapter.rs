@@ -0,0 +1,9 @@
+use joydb::adapters::{CborAdapter, CborPartitionedAdapter};
+
+mod unified {
+    test_suite::adapter_scenarios!(super::CborAdapter);
+}
+
+mod partitioned {
+    test_suite::adapter_scenarios!(super::CborPartitionedAdapter);
+}
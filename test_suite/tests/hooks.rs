@@ -0,0 +1,89 @@
+use joydb::{Joydb, JoydbError, Model, ModelHooks, ValidationError, adapters::JsonAdapter};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+#[joydb(hooks)]
+struct Account {
+    #[joydb(id, auto = "increment")]
+    id: u32,
+    #[joydb(unique)]
+    email: String,
+    balance: i64,
+    version: u32,
+}
+
+impl ModelHooks for Account {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.balance < 0 {
+            return Err(format!("balance of {} must not be negative", self.email).into());
+        }
+        Ok(())
+    }
+
+    fn before_insert(&mut self) {
+        self.email = self.email.to_lowercase();
+    }
+
+    fn before_update(&mut self, old: &Self) {
+        self.version = old.version + 1;
+    }
+}
+
+joydb::state! {
+    State,
+    models: [Account],
+}
+
+type Database = Joydb<State, JsonAdapter>;
+
+fn account(email: &str, balance: i64) -> Account {
+    Account {
+        id: 0,
+        email: email.to_string(),
+        balance,
+        version: 0,
+    }
+}
+
+#[test]
+fn should_run_hooks_on_insert_and_update() {
+    let db = Database::new_in_memory().unwrap();
+
+    let id = db.insert_new(&account("Alice@Example.com", 10)).unwrap();
+    let alice = db.get::<Account>(&id).unwrap().unwrap();
+    assert_eq!(alice.email, "alice@example.com");
+    assert_eq!(alice.version, 0);
+
+    // The normalized email is checked for uniqueness
+    let err = db.insert_new(&account("ALICE@example.com", 0)).unwrap_err();
+    assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+
+    db.update_with::<Account, _>(&id, |alice| alice.balance = 20)
+        .unwrap();
+    let alice = db.get::<Account>(&id).unwrap().unwrap();
+    assert_eq!(alice.balance, 20);
+    assert_eq!(alice.version, 1);
+}
+
+#[test]
+fn should_reject_invalid_records() {
+    let db = Database::new_in_memory().unwrap();
+
+    let err = db.insert_new(&account("bob@example.com", -1)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid Account: balance of bob@example.com must not be negative"
+    );
+    assert_eq!(db.count::<Account>().unwrap(), 0);
+
+    let id = db.insert_new(&account("bob@example.com", 5)).unwrap();
+    let result = db.transaction(|tx| {
+        tx.update_with::<Account, _>(&id, |bob| bob.balance -= 3)?;
+        tx.update_with::<Account, _>(&id, |bob| bob.balance -= 3)
+    });
+    assert!(matches!(result, Err(JoydbError::Validation { .. })));
+
+    let bob = db.get::<Account>(&id).unwrap().unwrap();
+    assert_eq!(bob.balance, 5);
+    assert_eq!(bob.version, 0);
+}
@@ -0,0 +1,262 @@
+use joydb::adapters::JsonLogAdapter;
+use joydb::{Change, Joydb, JoydbConfig, JoydbError, JoydbMode, Model, SyncPolicy};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use test_suite::database::{DatabaseState, Tag, User};
+use test_suite::helpers::with_db_file_path;
+use uuid::Uuid;
+
+type LogDatabase = Joydb<DatabaseState, JsonLogAdapter>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+struct Invoice {
+    id: u32,
+    #[joydb(soft_delete)]
+    deleted: bool,
+}
+
+joydb::state! {
+    InvoiceState,
+    models: [Invoice],
+}
+
+/// Opens a log database at the given path, and removes the log file afterwards.
+fn with_log_db_path<F>(f: F)
+where
+    F: FnOnce(&str, &str),
+{
+    with_db_file_path(|file_path| {
+        let log_path = format!("{file_path}.log");
+        f(file_path, &log_path);
+        let _ = std::fs::remove_file(&log_path);
+    });
+}
+
+fn new_user(name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        age: 30,
+    }
+}
+
+fn count_lines(path: &str) -> usize {
+    std::fs::read_to_string(path).unwrap().lines().count()
+}
+
+#[test]
+fn should_append_changes_to_log_and_replay_them_on_open() {
+    with_log_db_path(|file_path, log_path| {
+        let alice = new_user("Alice");
+        let bob = new_user("Bob");
+        {
+            let db = LogDatabase::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&bob).unwrap();
+            db.update(&User {
+                age: 31,
+                ..alice.clone()
+            })
+            .unwrap();
+            db.delete::<User>(&bob.id).unwrap();
+        }
+
+        assert_eq!(count_lines(log_path), 4);
+
+        let db = LogDatabase::open(file_path).unwrap();
+        let users = db.get_all::<User>().unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].id, alice.id);
+        assert_eq!(users[0].age, 31);
+    });
+}
+
+#[test]
+fn should_replay_purge_of_soft_deleted_record() {
+    with_log_db_path(|file_path, _log_path| {
+        {
+            let db = Joydb::<InvoiceState, JsonLogAdapter>::open(file_path).unwrap();
+            db.insert(&Invoice {
+                id: 1,
+                deleted: false,
+            })
+            .unwrap();
+            db.delete::<Invoice>(&1).unwrap();
+            db.purge::<Invoice>(&1).unwrap();
+        }
+
+        let db = Joydb::<InvoiceState, JsonLogAdapter>::open(file_path).unwrap();
+        assert_eq!(db.get_with_deleted::<Invoice>(&1).unwrap(), None);
+    });
+}
+
+#[test]
+fn should_compact_log_into_snapshot_when_threshold_is_reached() {
+    with_log_db_path(|file_path, log_path| {
+        let adapter = JsonLogAdapter::new(file_path).with_compaction_threshold(300);
+        let db =
+            LogDatabase::open_with_config(joydb::JoydbConfig::new(joydb::JoydbMode::Persistent {
+                adapter,
+                sync_policy: joydb::SyncPolicy::Instant,
+            }))
+            .unwrap();
+
+        for i in 0..10 {
+            db.insert(&new_user(&format!("User {i}"))).unwrap();
+        }
+        drop(db);
+
+        let log_size = std::fs::metadata(log_path).unwrap().len();
+        assert!(log_size <= 300);
+
+        let db = LogDatabase::open(file_path).unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 10);
+    });
+}
+
+#[test]
+fn should_replay_log_with_incomplete_last_line() {
+    with_log_db_path(|file_path, log_path| {
+        let alice = new_user("Alice");
+        {
+            let db = LogDatabase::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+        }
+
+        // Simulate a crash in the middle of writing a line
+        let mut content = std::fs::read_to_string(log_path).unwrap();
+        content.push_str(r#"{"op":"insert","model":"User","rec"#);
+        std::fs::write(log_path, content).unwrap();
+
+        let db = LogDatabase::open(file_path).unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 1);
+
+        // The next write compacts the log instead of appending to the broken line
+        db.insert(&new_user("Bob")).unwrap();
+        drop(db);
+        assert_eq!(count_lines(log_path), 0);
+
+        let db = LogDatabase::open(file_path).unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 2);
+    });
+}
+
+#[test]
+fn should_write_only_committed_transaction_changes() {
+    with_log_db_path(|file_path, log_path| {
+        let db = LogDatabase::open(file_path).unwrap();
+        let rust = Tag {
+            id: Uuid::new_v4(),
+            name: "rust".to_string(),
+        };
+        db.insert(&rust).unwrap();
+
+        let result = db.transaction(|tx| {
+            tx.insert(&Tag {
+                id: Uuid::new_v4(),
+                name: "go".to_string(),
+            })?;
+            // Violates the unique constraint
+            tx.insert(&Tag {
+                id: Uuid::new_v4(),
+                name: "rust".to_string(),
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(count_lines(log_path), 1);
+
+        db.update_with::<Tag, _>(&rust.id, |tag| tag.name = "ferris".to_string())
+            .unwrap();
+        drop(db);
+
+        let db = LogDatabase::open(file_path).unwrap();
+        let tags = db.get_all::<Tag>().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "ferris");
+    });
+}
+
+#[test]
+fn should_discard_changes_and_events_of_transaction_which_failed_to_flush() {
+    with_log_db_path(|file_path, _log_path| {
+        let config = JoydbConfig::new(JoydbMode::Persistent {
+            adapter: JsonLogAdapter::new(file_path),
+            sync_policy: SyncPolicy::Instant,
+        });
+        let db = LogDatabase::open_with_config(config.with_detect_external_changes(true)).unwrap();
+        let receiver = db.subscribe::<User>();
+        db.insert(&new_user("Alice")).unwrap();
+        assert!(receiver.try_recv().is_ok());
+
+        // Make the flush on commit fail, as if the snapshot was modified by another program
+        let snapshot = std::fs::read(file_path).unwrap();
+        let modified_at = std::fs::metadata(file_path).unwrap().modified().unwrap();
+        std::fs::write(file_path, [snapshot.as_slice(), b" "].concat()).unwrap();
+
+        let err = db
+            .transaction(|tx| tx.insert(&new_user("Bob")))
+            .unwrap_err();
+        assert!(matches!(err, JoydbError::ModifiedExternally(_)));
+        assert!(receiver.try_recv().is_err());
+
+        // Undo the modification, so the next flush succeeds
+        std::fs::write(file_path, &snapshot).unwrap();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(file_path)
+            .unwrap();
+        file.set_modified(modified_at).unwrap();
+
+        db.insert(&new_user("Carol")).unwrap();
+        let Ok(Change::Inserted(carol)) = receiver.try_recv() else {
+            panic!("expected the insert of Carol");
+        };
+        assert_eq!(carol.name, "Carol");
+        drop(db);
+
+        let db = LogDatabase::open(file_path).unwrap();
+        let mut names: Vec<String> = db
+            .get_all::<User>()
+            .unwrap()
+            .into_iter()
+            .map(|user| user.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["Alice", "Carol"]);
+    });
+}
+
+#[test]
+fn should_detect_changes_appended_to_log_by_another_program() {
+    with_log_db_path(|file_path, log_path| {
+        let config = JoydbConfig::new(JoydbMode::Persistent {
+            adapter: JsonLogAdapter::new(file_path),
+            sync_policy: SyncPolicy::Instant,
+        });
+        let db = LogDatabase::open_with_config(config.with_detect_external_changes(true)).unwrap();
+        db.insert(&new_user("Alice")).unwrap();
+
+        // Another program appends an insert to the log, leaving the snapshot untouched
+        let bob = new_user("Bob");
+        let line = serde_json::json!({"op": "insert", "model": "User", "record": bob});
+        let mut log = std::fs::File::options()
+            .append(true)
+            .open(log_path)
+            .unwrap();
+        writeln!(log, "{line}").unwrap();
+        drop(log);
+
+        let err = db.insert(&new_user("Carol")).unwrap_err();
+        assert!(matches!(err, JoydbError::ModifiedExternally(_)));
+
+        db.force_reload().unwrap();
+        let mut names: Vec<String> = db
+            .get_all::<User>()
+            .unwrap()
+            .into_iter()
+            .map(|user| user.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["Alice", "Bob"]);
+    });
+}
@@ -0,0 +1,62 @@
+use joydb::adapters::{JsonAdapter, JsonPartitionedAdapter};
+use joydb::{Joydb, JoydbError};
+use std::path::Path;
+use test_suite::database::{Database, DatabaseState, User};
+use test_suite::helpers::with_db_file_path;
+use uuid::Uuid;
+
+#[test]
+fn should_not_open_database_that_is_already_open() {
+    with_db_file_path(|file_path| {
+        let db = Database::open(file_path).unwrap();
+
+        let Err(err) = Database::open(file_path) else {
+            panic!("expected the database to be locked");
+        };
+        assert!(matches!(err, JoydbError::Locked(_)));
+        assert!(
+            err.to_string()
+                .ends_with(".json.lock is locked by another process")
+        );
+
+        drop(db);
+        Database::open(file_path).unwrap();
+    });
+}
+
+#[test]
+fn should_not_lock_in_memory_database() {
+    let _first = Database::new_in_memory().unwrap();
+    let _second = Database::new_in_memory().unwrap();
+}
+
+#[test]
+fn should_create_missing_parent_directories() {
+    let root = format!("data/test-{}", Uuid::new_v4());
+    let dir_path = format!("{root}/partitioned/db");
+    let file_path = format!("{root}/unified/db.json");
+    let alice = User {
+        id: Uuid::new_v4(),
+        name: "Alice".to_string(),
+        age: 30,
+    };
+    {
+        let db = Joydb::<DatabaseState, JsonPartitionedAdapter>::open(&dir_path).unwrap();
+        db.insert(&alice).unwrap();
+        let db = Joydb::<DatabaseState, JsonAdapter>::open(&file_path).unwrap();
+        db.insert(&alice).unwrap();
+    }
+    assert!(Path::new(&format!("{dir_path}/User.json")).is_file());
+    assert!(Path::new(&file_path).is_file());
+
+    // An error names the path that could not be created
+    let blocker = format!("{root}/blocker");
+    std::fs::write(&blocker, "").unwrap();
+    let Err(err) = Joydb::<DatabaseState, JsonAdapter>::open(format!("{blocker}/db.json")) else {
+        panic!("expected the parent directory to be impossible to create");
+    };
+    assert!(matches!(err, JoydbError::Io(_)));
+    assert!(err.to_string().contains(&blocker), "{err}");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
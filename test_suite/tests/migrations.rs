@@ -0,0 +1,155 @@
+use joydb::adapters::{JsonAdapter, JsonPartitionedAdapter};
+use joydb::migration::Value;
+use joydb::{Joydb, JoydbError};
+use test_suite::helpers::{gen_db_file_path, with_db_file_path};
+
+/// The schema before any migration.
+mod v0 {
+    use joydb::Model;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    pub struct User {
+        pub id: u32,
+        pub name: String,
+    }
+
+    joydb::state! {
+        State,
+        models: [User],
+    }
+}
+
+/// The schema after `email` was added and `name` was split into `first_name` and `last_name`.
+mod v2 {
+    use super::*;
+    use joydb::Model;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, Model)]
+    pub struct User {
+        pub id: u32,
+        pub first_name: String,
+        pub last_name: String,
+        pub email: String,
+    }
+
+    fn add_email(users: &mut Value) -> Result<(), JoydbError> {
+        for user in users.as_array_mut().into_iter().flatten() {
+            user["email"] = Value::from("");
+        }
+        Ok(())
+    }
+
+    fn split_name(users: &mut Value) -> Result<(), JoydbError> {
+        for user in users.as_array_mut().into_iter().flatten() {
+            let name = user["name"].as_str().unwrap_or_default().to_string();
+            let (first_name, last_name) = name.split_once(' ').unwrap_or((&name, ""));
+            user["first_name"] = Value::from(first_name);
+            user["last_name"] = Value::from(last_name);
+        }
+        Ok(())
+    }
+
+    joydb::state! {
+        State,
+        models: [User],
+        migrations: [
+            User => add_email,
+            User => split_name,
+        ],
+    }
+}
+
+fn read_json(file_path: &str) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(file_path).unwrap()).unwrap()
+}
+
+#[test]
+fn should_migrate_data_and_upgrade_file_on_flush() {
+    with_db_file_path(|file_path| {
+        {
+            let db = Joydb::<v0::State, JsonAdapter>::open(file_path).unwrap();
+            let alice = v0::User {
+                id: 1,
+                name: "Alice Smith".to_string(),
+            };
+            db.insert(&alice).unwrap();
+        }
+
+        let db = Joydb::<v2::State, JsonAdapter>::open(file_path).unwrap();
+        let alice = db.get::<v2::User>(&1).unwrap().unwrap();
+        assert_eq!(alice.first_name, "Alice");
+        assert_eq!(alice.last_name, "Smith");
+        assert_eq!(alice.email, "");
+
+        // The file is not upgraded until the first flush
+        assert_eq!(read_json(file_path).get("schema_version"), None);
+        db.flush().unwrap();
+        drop(db);
+
+        let json = read_json(file_path);
+        assert_eq!(json["schema_version"], 2);
+        assert_eq!(json["User"][0]["first_name"], "Alice");
+
+        // The upgraded file is loaded as is
+        let db = Joydb::<v2::State, JsonAdapter>::open(file_path).unwrap();
+        assert_eq!(db.count::<v2::User>().unwrap(), 1);
+    });
+}
+
+#[test]
+fn should_reload_migrated_data_in_read_only_mode() {
+    with_db_file_path(|file_path| {
+        {
+            let db = Joydb::<v0::State, JsonAdapter>::open(file_path).unwrap();
+            db.insert(&v0::User {
+                id: 1,
+                name: "Alice Smith".to_string(),
+            })
+            .unwrap();
+        }
+
+        let db = Joydb::<v2::State, JsonAdapter>::open_read_only(file_path).unwrap();
+        db.reload().unwrap();
+        assert_eq!(db.get::<v2::User>(&1).unwrap().unwrap().first_name, "Alice");
+        assert_ne!(read_json(file_path)["schema_version"], 2);
+    });
+}
+
+#[test]
+fn should_write_schema_version_of_new_database() {
+    with_db_file_path(|file_path| {
+        drop(Joydb::<v2::State, JsonAdapter>::open(file_path).unwrap());
+        assert_eq!(read_json(file_path)["schema_version"], 2);
+    });
+}
+
+#[test]
+fn should_refuse_data_of_newer_schema_version() {
+    with_db_file_path(|file_path| {
+        std::fs::write(file_path, r#"{ "schema_version": 3, "User": [] }"#).unwrap();
+
+        let Err(err) = Joydb::<v2::State, JsonAdapter>::open(file_path) else {
+            panic!("expected the schema version to be refused");
+        };
+        assert!(matches!(err, JoydbError::Deserialize(_)));
+        assert!(err.to_string().contains("schema version 3 is newer"));
+    });
+}
+
+#[test]
+fn should_refuse_migrations_with_partitioned_adapter() {
+    let dir_path = gen_db_file_path();
+
+    let Err(err) = Joydb::<v2::State, JsonPartitionedAdapter>::open(&dir_path) else {
+        panic!("expected the migrations to be refused");
+    };
+    assert!(matches!(err, JoydbError::Deserialize(_)));
+    assert!(
+        err.to_string()
+            .contains("not supported by partitioned adapters")
+    );
+
+    std::fs::remove_file(format!("{dir_path}.lock")).unwrap();
+}
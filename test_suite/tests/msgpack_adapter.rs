@@ -0,0 +1,9 @@
+use joydb::adapters::{MessagePackAdapter, MessagePackPartitionedAdapter};
+
+mod unified {
+    test_suite::adapter_scenarios!(super::MessagePackAdapter);
+}
+
+mod partitioned {
+    test_suite::adapter_scenarios!(super::MessagePackPartitionedAdapter);
+}
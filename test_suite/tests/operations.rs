@@ -221,3 +221,337 @@ mod upsert {
         });
     }
 }
+
+mod get_all_by_index {
+    use super::*;
+    use test_suite::database::PostIndex;
+
+    fn new_post(user_id: Uuid, content: &str) -> Post {
+        Post {
+            id: Uuid::new_v4(),
+            content: content.to_string(),
+            user_id,
+        }
+    }
+
+    #[test]
+    fn should_return_records_with_matching_indexed_field() {
+        with_open_db(|db| {
+            let alice_id = Uuid::new_v4();
+            let bob_id = Uuid::new_v4();
+
+            db.insert(&new_post(alice_id, "Alice 1")).unwrap();
+            db.insert(&new_post(bob_id, "Bob 1")).unwrap();
+            db.insert(&new_post(alice_id, "Alice 2")).unwrap();
+
+            let mut alice_posts = db
+                .get_all_by_index::<Post, _>(PostIndex::UserId, &alice_id)
+                .unwrap();
+            alice_posts.sort_by(|a, b| a.content.cmp(&b.content));
+            let contents: Vec<&str> = alice_posts.iter().map(|p| p.content.as_str()).collect();
+            assert_eq!(contents, vec!["Alice 1", "Alice 2"]);
+
+            let unknown_posts = db
+                .get_all_by_index::<Post, _>(PostIndex::UserId, &Uuid::new_v4())
+                .unwrap();
+            assert!(unknown_posts.is_empty());
+        });
+    }
+
+    #[test]
+    fn should_reflect_updates_of_indexed_field() {
+        with_open_db(|db| {
+            let alice_id = Uuid::new_v4();
+            let bob_id = Uuid::new_v4();
+
+            let mut post = new_post(alice_id, "Hello");
+            db.insert(&post).unwrap();
+
+            post.user_id = bob_id;
+            db.update(&post).unwrap();
+
+            let alice_posts = db
+                .get_all_by_index::<Post, _>(PostIndex::UserId, &alice_id)
+                .unwrap();
+            assert!(alice_posts.is_empty());
+
+            let bob_posts = db
+                .get_all_by_index::<Post, _>(PostIndex::UserId, &bob_id)
+                .unwrap();
+            assert_eq!(bob_posts.len(), 1);
+        });
+    }
+}
+
+mod unique {
+    use super::*;
+    use test_suite::database::{Tag, TagIndex};
+
+    fn new_tag(name: &str) -> Tag {
+        Tag {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_return_error_on_attempt_to_insert_record_with_taken_unique_value() {
+        with_open_db(|db| {
+            db.insert(&new_tag("rust")).unwrap();
+
+            let err = db.insert(&new_tag("rust")).unwrap_err();
+            assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+            assert_eq!(err.to_string(), r#"Tag with name = "rust" already exists"#);
+
+            assert_eq!(db.count::<Tag>().unwrap(), 1);
+        });
+    }
+
+    #[test]
+    fn should_return_error_on_attempt_to_update_record_with_taken_unique_value() {
+        with_open_db(|db| {
+            db.insert(&new_tag("rust")).unwrap();
+            let mut tag = new_tag("go");
+            db.insert(&tag).unwrap();
+
+            tag.name = "rust".to_string();
+            let err = db.update(&tag).unwrap_err();
+            assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+
+            let err = db.upsert(&tag).unwrap_err();
+            assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+
+            let go_tags = db
+                .get_all_by_index::<Tag, _>(TagIndex::Name, &"go".to_string())
+                .unwrap();
+            assert_eq!(go_tags.len(), 1);
+        });
+    }
+}
+
+mod composite_id {
+    use super::*;
+    use test_suite::database::PostTag;
+
+    #[test]
+    fn should_get_and_delete_by_composite_id() {
+        with_open_db(|db| {
+            let post_id = Uuid::new_v4();
+            let rust_tag_id = Uuid::new_v4();
+            let go_tag_id = Uuid::new_v4();
+
+            db.insert(&PostTag {
+                post_id,
+                tag_id: rust_tag_id,
+            })
+            .unwrap();
+            db.insert(&PostTag {
+                post_id,
+                tag_id: go_tag_id,
+            })
+            .unwrap();
+
+            let post_tag = db.get::<PostTag>(&(post_id, rust_tag_id)).unwrap().unwrap();
+            assert_eq!(post_tag.tag_id, rust_tag_id);
+
+            let deleted = db.delete::<PostTag>(&(post_id, go_tag_id)).unwrap();
+            assert!(deleted.is_some());
+            assert_eq!(db.count::<PostTag>().unwrap(), 1);
+            assert!(db.get::<PostTag>(&(post_id, go_tag_id)).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn should_return_error_on_attempt_to_insert_duplicated_pair() {
+        with_open_db(|db| {
+            let post_tag = PostTag {
+                post_id: Uuid::new_v4(),
+                tag_id: Uuid::new_v4(),
+            };
+            db.insert(&post_tag).unwrap();
+
+            let err = db.insert(&post_tag).unwrap_err();
+            assert!(matches!(err, JoydbError::DuplicatedId { .. }));
+        });
+    }
+}
+
+mod insert_new {
+    use super::*;
+    use test_suite::database::{Comment, Database, Tag};
+    use test_suite::helpers::with_db_file_path;
+
+    fn new_comment(text: &str) -> Comment {
+        Comment {
+            id: 0,
+            post_id: Uuid::new_v4(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_generate_sequential_ids() {
+        with_open_db(|db| {
+            let first_id = db.insert_new(&new_comment("First")).unwrap();
+            let second_id = db.insert_new(&new_comment("Second")).unwrap();
+            assert_eq!(first_id, 1);
+            assert_eq!(second_id, 2);
+
+            let second = db.get::<Comment>(&2).unwrap().unwrap();
+            assert_eq!(second.id, 2);
+            assert_eq!(second.text, "Second");
+        });
+    }
+
+    #[test]
+    fn should_skip_ids_that_are_already_taken() {
+        with_open_db(|db| {
+            let mut comment = new_comment("Manual");
+            comment.id = 2;
+            db.insert(&comment).unwrap();
+
+            assert_eq!(db.insert_new(&new_comment("First")).unwrap(), 3);
+        });
+    }
+
+    #[test]
+    fn should_continue_counter_after_reopening() {
+        with_db_file_path(|file_path| {
+            {
+                let db = Database::open(file_path).unwrap();
+                db.insert_new(&new_comment("First")).unwrap();
+                db.insert_new(&new_comment("Second")).unwrap();
+            }
+
+            let db = Database::open(file_path).unwrap();
+            assert_eq!(db.insert_new(&new_comment("Third")).unwrap(), 3);
+        });
+    }
+
+    #[test]
+    fn should_not_reuse_id_of_deleted_record_after_reopening() {
+        with_db_file_path(|file_path| {
+            {
+                let db = Database::open(file_path).unwrap();
+                db.insert_new(&new_comment("First")).unwrap();
+                db.insert_new(&new_comment("Second")).unwrap();
+                db.delete::<Comment>(&2).unwrap();
+            }
+
+            let db = Database::open(file_path).unwrap();
+            assert_eq!(db.insert_new(&new_comment("Third")).unwrap(), 3);
+        });
+    }
+
+    #[test]
+    fn should_generate_uuid() {
+        with_open_db(|db| {
+            let tag = Tag {
+                id: Uuid::nil(),
+                name: "rust".to_string(),
+            };
+            let id = db.insert_new(&tag).unwrap();
+            assert_ne!(id, Uuid::nil());
+            assert_eq!(db.get::<Tag>(&id).unwrap().unwrap().name, "rust");
+        });
+    }
+}
+
+mod borrowing_reads {
+    use super::*;
+
+    fn insert_users(db: &test_suite::database::Database) {
+        for (name, age) in [("Alice", 30), ("Bob", 25), ("Carol", 41)] {
+            let user = User {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                age,
+            };
+            db.insert(&user).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_aggregate_records_with_relation() {
+        with_open_db(|db| {
+            insert_users(&db);
+
+            let total_age: u32 = db
+                .with_relation(|users: &[User]| users.iter().map(|u| u32::from(u.age)).sum())
+                .unwrap();
+            assert_eq!(total_age, 96);
+        });
+    }
+
+    #[test]
+    fn should_count_and_check_existence_by_predicate() {
+        with_open_db(|db| {
+            insert_users(&db);
+
+            assert_eq!(db.count_by(|u: &User| u.age > 26).unwrap(), 2);
+            assert!(db.exists_by(|u: &User| u.name == "Bob").unwrap());
+            assert!(!db.exists_by(|u: &User| u.name == "Dave").unwrap());
+        });
+    }
+}
+
+mod update_with {
+    use super::*;
+    use test_suite::database::Tag;
+
+    fn new_tag(name: &str) -> Tag {
+        Tag {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_update_record_in_place() {
+        with_open_db(|db| {
+            let tag = new_tag("rust");
+            db.insert(&tag).unwrap();
+
+            db.update_with::<Tag, _>(&tag.id, |tag| tag.name = "ferris".to_string())
+                .unwrap();
+
+            let same_tag = db.get::<Tag>(&tag.id).unwrap().unwrap();
+            assert_eq!(same_tag.name, "ferris");
+        });
+    }
+
+    #[test]
+    fn should_reject_change_that_violates_unique_constraint() {
+        with_open_db(|db| {
+            let rust = new_tag("rust");
+            let go = new_tag("go");
+            db.insert(&rust).unwrap();
+            db.insert(&go).unwrap();
+
+            let err = db
+                .update_with::<Tag, _>(&go.id, |tag| tag.name = "rust".to_string())
+                .unwrap_err();
+            assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+            assert_eq!(db.get::<Tag>(&go.id).unwrap().unwrap().name, "go");
+        });
+    }
+
+    #[test]
+    fn should_update_all_matching_records() {
+        with_open_db(|db| {
+            for name in ["rust", "rustc", "go"] {
+                db.insert(&new_tag(name)).unwrap();
+            }
+
+            let changed_count = db
+                .update_all_by(
+                    |tag: &Tag| tag.name.starts_with("rust"),
+                    |tag| tag.name = tag.name.to_uppercase(),
+                )
+                .unwrap();
+            assert_eq!(changed_count, 2);
+            assert!(db.exists_by(|tag: &Tag| tag.name == "RUSTC").unwrap());
+            assert!(db.exists_by(|tag: &Tag| tag.name == "go").unwrap());
+        });
+    }
+}
@@ -0,0 +1,121 @@
+use joydb::adapters::{Adapter, Partitioned, PartitionedAdapter};
+use joydb::{
+    Changeset, Joydb, JoydbConfig, JoydbError, JoydbMode, Model, Relation, State, SyncPolicy,
+};
+use std::sync::{Arc, Mutex};
+use test_suite::database::{Comment, DatabaseState, Tag, User};
+use uuid::Uuid;
+
+/// A partitioned adapter, that only remembers the changesets it receives.
+#[derive(Default, Clone)]
+struct ChangesetRecorder {
+    /// Model name and numbers of inserted, updated and deleted records, e.g. `User +1 ~0 -0`.
+    writes: Arc<Mutex<Vec<String>>>,
+}
+
+impl PartitionedAdapter for ChangesetRecorder {
+    fn write_relation<M: Model>(&self, _relation: &Relation<M>) -> Result<(), JoydbError> {
+        unreachable!("Changes must be written with write_relation_changes")
+    }
+
+    fn write_relation_changes<M: Model>(
+        &self,
+        relation: &Relation<M>,
+        changeset: &Changeset<M::Id>,
+    ) -> Result<(), JoydbError> {
+        for id in changeset.inserted().iter().chain(changeset.updated()) {
+            assert!(relation.record(id).is_some());
+        }
+        self.writes.lock().unwrap().push(format!(
+            "{} +{} ~{} -{}",
+            M::model_name(),
+            changeset.inserted().len(),
+            changeset.updated().len(),
+            changeset.deleted().len(),
+        ));
+        Ok(())
+    }
+
+    fn load_state<S: State>(&self) -> Result<S, JoydbError> {
+        S::load_with_partitioned_adapter(self)
+    }
+
+    fn load_relation<M: Model>(&self) -> Result<Relation<M>, JoydbError> {
+        Ok(Relation::default())
+    }
+}
+
+impl Adapter for ChangesetRecorder {
+    type Target = Partitioned<Self>;
+}
+
+type Db = Joydb<DatabaseState, ChangesetRecorder>;
+
+/// Opens a database which is flushed manually, returning the writes of its adapter.
+fn open_db() -> (Db, Arc<Mutex<Vec<String>>>) {
+    let adapter = ChangesetRecorder::default();
+    let writes = adapter.writes.clone();
+    let db = Db::open_with_config(JoydbConfig::new(JoydbMode::Persistent {
+        adapter,
+        sync_policy: SyncPolicy::Manual,
+    }))
+    .unwrap();
+    (db, writes)
+}
+
+#[test]
+fn should_pass_changes_since_last_flush_to_partitioned_adapter() {
+    let (db, writes) = open_db();
+
+    let alice = User {
+        id: Uuid::new_v4(),
+        name: "Alice".to_string(),
+        age: 30,
+    };
+    let bob = User {
+        id: Uuid::new_v4(),
+        name: "Bob".to_string(),
+        age: 25,
+    };
+    db.insert(&alice).unwrap();
+    db.insert(&bob).unwrap();
+    db.flush().unwrap();
+
+    db.update(&User { age: 31, ..alice }).unwrap();
+    db.delete::<User>(&bob.id).unwrap();
+    db.flush().unwrap();
+
+    assert_eq!(
+        *writes.lock().unwrap(),
+        vec!["User +2 ~0 -0", "User +0 ~1 -1"]
+    );
+}
+
+#[test]
+fn should_write_id_counters_only_once_increment_id_is_generated() {
+    let (db, writes) = open_db();
+
+    db.insert_new(&Tag {
+        id: Uuid::nil(),
+        name: "rust".to_string(),
+    })
+    .unwrap();
+    db.flush().unwrap();
+    assert_eq!(*writes.lock().unwrap(), vec!["Tag +1 ~0 -0"]);
+
+    db.insert_new(&Comment {
+        id: 0,
+        post_id: Uuid::new_v4(),
+        text: "Hello".to_string(),
+    })
+    .unwrap();
+    db.flush().unwrap();
+    assert_eq!(
+        *writes.lock().unwrap(),
+        vec![
+            "Tag +1 ~0 -0",
+            "Comment +1 ~0 -0",
+            "JoydbIdCounter +1 ~0 -0"
+        ]
+    );
+}
@@ -0,0 +1,114 @@
+use std::cell::Cell;
+use test_suite::database::Comment;
+use test_suite::helpers::with_open_db;
+use uuid::Uuid;
+
+fn insert_comments(db: &test_suite::database::Database, post_id: Uuid, texts: &[&str]) {
+    for text in texts {
+        let comment = Comment {
+            id: 0,
+            post_id,
+            text: text.to_string(),
+        };
+        db.insert_new(&comment).unwrap();
+    }
+}
+
+fn texts(comments: &[Comment]) -> Vec<&str> {
+    comments.iter().map(|c| c.text.as_str()).collect()
+}
+
+#[test]
+fn should_order_by_key() {
+    with_open_db(|db| {
+        insert_comments(&db, Uuid::new_v4(), &["b", "c", "a"]);
+
+        let comments = db
+            .query::<Comment>()
+            .order_by(|c| c.text.clone())
+            .fetch()
+            .unwrap();
+        assert_eq!(texts(&comments), vec!["a", "b", "c"]);
+
+        let comments = db
+            .query::<Comment>()
+            .order_by_desc(|c| c.text.clone())
+            .fetch()
+            .unwrap();
+        assert_eq!(texts(&comments), vec!["c", "b", "a"]);
+    });
+}
+
+#[test]
+fn should_break_ties_with_next_order_key() {
+    with_open_db(|db| {
+        insert_comments(&db, Uuid::new_v4(), &["b", "a", "b", "a"]);
+
+        let comments = db
+            .query::<Comment>()
+            .order_by(|c| c.text.clone())
+            .order_by_desc(|c| c.id)
+            .fetch()
+            .unwrap();
+        let ids: Vec<u32> = comments.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![4, 2, 3, 1]);
+    });
+}
+
+#[test]
+fn should_compute_order_key_once_per_record() {
+    with_open_db(|db| {
+        insert_comments(&db, Uuid::new_v4(), &["d", "b", "e", "a", "c"]);
+
+        let calls = Cell::new(0);
+        let comments = db
+            .query::<Comment>()
+            .order_by(|c| {
+                calls.set(calls.get() + 1);
+                c.text.to_uppercase()
+            })
+            .fetch()
+            .unwrap();
+        assert_eq!(texts(&comments), vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(calls.get(), 5);
+    });
+}
+
+#[test]
+fn should_filter_and_paginate() {
+    with_open_db(|db| {
+        let post_id = Uuid::new_v4();
+        insert_comments(&db, post_id, &["1", "2", "3", "4", "5"]);
+        insert_comments(&db, Uuid::new_v4(), &["other"]);
+
+        let page = |offset| {
+            db.query::<Comment>()
+                .filter(|c| c.post_id == post_id)
+                .order_by(|c| c.id)
+                .offset(offset)
+                .limit(2)
+                .fetch()
+                .unwrap()
+        };
+
+        assert_eq!(texts(&page(0)), vec!["1", "2"]);
+        assert_eq!(texts(&page(2)), vec!["3", "4"]);
+        assert_eq!(texts(&page(4)), vec!["5"]);
+        assert!(page(6).is_empty());
+    });
+}
+
+#[test]
+fn should_combine_multiple_filters() {
+    with_open_db(|db| {
+        insert_comments(&db, Uuid::new_v4(), &["apple", "avocado", "banana"]);
+
+        let comments = db
+            .query::<Comment>()
+            .filter(|c| c.text.starts_with('a'))
+            .filter(|c| c.text.len() > 5)
+            .fetch()
+            .unwrap();
+        assert_eq!(texts(&comments), vec!["avocado"]);
+    });
+}
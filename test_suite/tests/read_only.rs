@@ -0,0 +1,79 @@
+use joydb::JoydbError;
+use std::path::Path;
+use test_suite::database::{Database, User};
+use test_suite::helpers::{gen_db_file_path, with_db_file_path};
+use uuid::Uuid;
+
+fn new_user(name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        age: 30,
+    }
+}
+
+#[test]
+fn should_read_but_not_modify_data() {
+    with_db_file_path(|file_path| {
+        let alice = new_user("Alice");
+        Database::open(file_path).unwrap().insert(&alice).unwrap();
+        let content = std::fs::read_to_string(file_path).unwrap();
+
+        {
+            let db = Database::open_read_only(file_path).unwrap();
+            let user: User = db.get(&alice.id).unwrap().unwrap();
+            assert_eq!(user.name, "Alice");
+
+            let err = db.insert(&new_user("Bob")).unwrap_err();
+            assert!(matches!(err, JoydbError::ReadOnly));
+
+            let err = db.delete::<User>(&alice.id).unwrap_err();
+            assert!(matches!(err, JoydbError::ReadOnly));
+
+            let err = db
+                .transaction(|tx| tx.upsert(&new_user("Bob")))
+                .unwrap_err();
+            assert!(matches!(err, JoydbError::ReadOnly));
+
+            db.flush().unwrap();
+            assert_eq!(db.count::<User>().unwrap(), 1);
+        }
+
+        assert_eq!(std::fs::read_to_string(file_path).unwrap(), content);
+    });
+}
+
+#[test]
+fn should_not_create_missing_data() {
+    let file_path = gen_db_file_path();
+
+    let Err(err) = Database::open_read_only(&file_path) else {
+        panic!("expected the database to be missing");
+    };
+    assert!(matches!(err, JoydbError::Io(_)));
+    assert!(!Path::new(&file_path).exists());
+    assert!(!Path::new(&format!("{file_path}.lock")).exists());
+}
+
+#[test]
+fn should_share_lock_between_readers_only() {
+    with_db_file_path(|file_path| {
+        drop(Database::open(file_path).unwrap());
+
+        let reader = Database::open_read_only(file_path).unwrap();
+        let other_reader = Database::open_read_only(file_path).unwrap();
+        assert!(matches!(
+            Database::open(file_path),
+            Err(JoydbError::Locked(_))
+        ));
+
+        drop(reader);
+        drop(other_reader);
+        let writer = Database::open(file_path).unwrap();
+        assert!(matches!(
+            Database::open_read_only(file_path),
+            Err(JoydbError::Locked(_))
+        ));
+        drop(writer);
+    });
+}
@@ -0,0 +1,87 @@
+use joydb::{JoydbConfig, JoydbError, JoydbMode, SyncPolicy, adapters::JsonAdapter};
+use test_suite::database::{Database, User};
+use test_suite::helpers::with_db_file_path;
+use uuid::Uuid;
+
+fn new_user(name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        age: 30,
+    }
+}
+
+fn open_db(file_path: &str, sync_policy: SyncPolicy, detect_external_changes: bool) -> Database {
+    let config = JoydbConfig::new(JoydbMode::Persistent {
+        adapter: JsonAdapter::new(file_path, true),
+        sync_policy,
+    });
+    Database::open_with_config(config.with_detect_external_changes(detect_external_changes))
+        .unwrap()
+}
+
+/// Simulates editing the data file by hand.
+fn rename_in_file(file_path: &str, from: &str, to: &str) {
+    let content = std::fs::read_to_string(file_path).unwrap();
+    std::fs::write(file_path, content.replace(from, to)).unwrap();
+}
+
+fn get_name(db: &Database, id: &Uuid) -> String {
+    db.get::<User>(id).unwrap().unwrap().name
+}
+
+#[test]
+fn should_reload_state_modified_externally() {
+    with_db_file_path(|file_path| {
+        let db = open_db(file_path, SyncPolicy::Instant, false);
+        let alice = new_user("Alice");
+        db.insert(&alice).unwrap();
+
+        rename_in_file(file_path, "Alice", "Alicia");
+        assert_eq!(get_name(&db, &alice.id), "Alice");
+
+        db.reload().unwrap();
+        assert_eq!(get_name(&db, &alice.id), "Alicia");
+    });
+}
+
+#[test]
+fn should_not_reload_with_unsaved_changes_unless_forced() {
+    with_db_file_path(|file_path| {
+        let db = open_db(file_path, SyncPolicy::Manual, false);
+        let alice = new_user("Alice");
+        db.insert(&alice).unwrap();
+
+        let err = db.reload().unwrap_err();
+        assert!(matches!(err, JoydbError::UnsavedChanges));
+        assert_eq!(db.count::<User>().unwrap(), 1);
+
+        db.force_reload().unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 0);
+    });
+}
+
+#[test]
+fn should_not_overwrite_data_modified_externally() {
+    with_db_file_path(|file_path| {
+        let db = open_db(file_path, SyncPolicy::Instant, true);
+        let alice = new_user("Alice");
+        db.insert(&alice).unwrap();
+
+        rename_in_file(file_path, "Alice", "Alicia");
+
+        let err = db.insert(&new_user("Bob")).unwrap_err();
+        assert!(matches!(err, JoydbError::ModifiedExternally(_)));
+        let content = std::fs::read_to_string(file_path).unwrap();
+        assert!(content.contains("Alicia"));
+        assert!(!content.contains("Bob"));
+
+        db.force_reload().unwrap();
+        assert_eq!(get_name(&db, &alice.id), "Alicia");
+
+        // Own writes are not treated as external changes
+        db.insert(&new_user("Bob")).unwrap();
+        db.insert(&new_user("Carol")).unwrap();
+        assert_eq!(db.count::<User>().unwrap(), 3);
+    });
+}
@@ -0,0 +1,207 @@
+use joydb::{Joydb, JoydbError, Model, adapters::JsonAdapter};
+use serde::{Deserialize, Serialize};
+use test_suite::helpers::with_db_file_path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+struct Account {
+    id: u32,
+    #[joydb(unique)]
+    email: String,
+    #[joydb(soft_delete)]
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+struct Invoice {
+    id: u32,
+    #[joydb(index)]
+    customer: String,
+    #[joydb(soft_delete)]
+    deleted: bool,
+}
+
+joydb::state! {
+    State,
+    models: [Invoice, Account],
+}
+
+type Database = Joydb<State, JsonAdapter>;
+
+fn invoice(id: u32, customer: &str) -> Invoice {
+    Invoice {
+        id,
+        customer: customer.to_string(),
+        deleted: false,
+    }
+}
+
+fn sample_db() -> Database {
+    let db = Database::new_in_memory().unwrap();
+    db.insert(&invoice(1, "acme")).unwrap();
+    db.insert(&invoice(2, "acme")).unwrap();
+    db.insert(&invoice(3, "globex")).unwrap();
+    db
+}
+
+#[test]
+fn should_hide_soft_deleted_records() {
+    let db = sample_db();
+
+    let deleted = db.delete::<Invoice>(&1).unwrap().unwrap();
+    assert!(deleted.deleted);
+    assert_eq!(db.delete::<Invoice>(&1).unwrap(), None);
+
+    assert_eq!(db.get::<Invoice>(&1).unwrap(), None);
+    assert_eq!(db.count::<Invoice>().unwrap(), 2);
+    assert_eq!(db.get_all::<Invoice>().unwrap().len(), 2);
+    assert_eq!(
+        db.get_all_by(|i: &Invoice| i.customer == "acme").unwrap(),
+        vec![invoice(2, "acme")]
+    );
+    assert_eq!(
+        db.get_all_by_index(InvoiceIndex::Customer, &"acme".to_string())
+            .unwrap(),
+        vec![invoice(2, "acme")]
+    );
+    assert_eq!(db.count_by(|i: &Invoice| i.id == 1).unwrap(), 0);
+    assert!(!db.exists_by(|i: &Invoice| i.id == 1).unwrap());
+    assert_eq!(db.query::<Invoice>().fetch().unwrap().len(), 2);
+
+    // The record is still there
+    assert_eq!(db.get_with_deleted::<Invoice>(&1).unwrap(), Some(deleted));
+    assert_eq!(
+        db.with_relation(|invoices: &[Invoice]| invoices.len())
+            .unwrap(),
+        3
+    );
+
+    // It's not possible to insert a record with the same id
+    let err = db.insert(&invoice(1, "initech")).unwrap_err();
+    assert!(matches!(err, JoydbError::DuplicatedId { .. }));
+}
+
+#[test]
+fn should_not_update_soft_deleted_records() {
+    let db = sample_db();
+    db.delete::<Invoice>(&1).unwrap();
+
+    let err = db.update(&invoice(1, "initech")).unwrap_err();
+    assert!(matches!(err, JoydbError::NotFound { .. }));
+    let err = db
+        .update_with::<Invoice, _>(&1, |i| i.customer = "initech".to_string())
+        .unwrap_err();
+    assert!(matches!(err, JoydbError::NotFound { .. }));
+
+    let changed = db
+        .update_all_by::<Invoice, _, _>(|_| true, |i| i.customer = "initech".to_string())
+        .unwrap();
+    assert_eq!(changed, 2);
+    let deleted = db.get_with_deleted::<Invoice>(&1).unwrap().unwrap();
+    assert_eq!(deleted.customer, "acme");
+}
+
+#[test]
+fn should_not_upsert_soft_deleted_record() {
+    let db = sample_db();
+    db.delete::<Invoice>(&1).unwrap();
+
+    let err = db.upsert(&invoice(1, "initech")).unwrap_err();
+    assert!(matches!(err, JoydbError::DuplicatedId { .. }));
+    assert_eq!(db.get::<Invoice>(&1).unwrap(), None);
+    assert_eq!(
+        db.get_with_deleted::<Invoice>(&1)
+            .unwrap()
+            .unwrap()
+            .customer,
+        "acme"
+    );
+}
+
+#[test]
+fn should_soft_delete_all_matching_records() {
+    let db = sample_db();
+
+    let deleted = db
+        .delete_all_by(|i: &Invoice| i.customer == "acme")
+        .unwrap();
+    assert_eq!(deleted.len(), 2);
+    assert!(deleted.iter().all(|i| i.deleted));
+    assert_eq!(db.get_all::<Invoice>().unwrap(), vec![invoice(3, "globex")]);
+}
+
+#[test]
+fn should_restore_soft_deleted_record() {
+    let db = sample_db();
+    db.delete::<Invoice>(&1).unwrap();
+
+    assert_eq!(db.restore::<Invoice>(&1).unwrap(), Some(invoice(1, "acme")));
+    assert_eq!(db.get::<Invoice>(&1).unwrap(), Some(invoice(1, "acme")));
+
+    // Records, which are not deleted, are not restored
+    assert_eq!(db.restore::<Invoice>(&1).unwrap(), None);
+    assert_eq!(db.restore::<Invoice>(&404).unwrap(), None);
+}
+
+#[test]
+fn should_purge_records() {
+    let db = sample_db();
+    db.delete::<Invoice>(&1).unwrap();
+
+    assert!(db.purge::<Invoice>(&1).unwrap().unwrap().deleted);
+    assert!(db.purge::<Invoice>(&2).unwrap().is_some());
+    assert_eq!(db.get_with_deleted::<Invoice>(&1).unwrap(), None);
+    assert_eq!(db.get_with_deleted::<Invoice>(&2).unwrap(), None);
+    assert_eq!(db.purge::<Invoice>(&1).unwrap(), None);
+
+    db.insert(&invoice(1, "initech")).unwrap();
+}
+
+fn account(id: u32, email: &str) -> Account {
+    Account {
+        id,
+        email: email.to_string(),
+        deleted: false,
+    }
+}
+
+#[test]
+fn should_release_unique_value_of_soft_deleted_record() {
+    let db = Database::new_in_memory().unwrap();
+    db.insert(&account(1, "alice@example.com")).unwrap();
+    db.delete::<Account>(&1).unwrap();
+
+    db.insert(&account(2, "alice@example.com")).unwrap();
+}
+
+#[test]
+fn should_not_restore_record_whose_unique_value_is_taken() {
+    let db = Database::new_in_memory().unwrap();
+    db.insert(&account(1, "alice@example.com")).unwrap();
+    db.delete::<Account>(&1).unwrap();
+    db.insert(&account(2, "alice@example.com")).unwrap();
+
+    let err = db.restore::<Account>(&1).unwrap_err();
+    assert!(matches!(err, JoydbError::UniqueViolation { .. }));
+    assert!(db.get_with_deleted::<Account>(&1).unwrap().unwrap().deleted);
+
+    db.delete::<Account>(&2).unwrap();
+    assert_eq!(
+        db.restore::<Account>(&1).unwrap(),
+        Some(account(1, "alice@example.com"))
+    );
+}
+
+#[test]
+fn should_persist_soft_delete_flag() {
+    with_db_file_path(|file_path| {
+        {
+            let db = Database::open(file_path).unwrap();
+            db.insert(&invoice(1, "acme")).unwrap();
+            db.delete::<Invoice>(&1).unwrap();
+        }
+
+        let db = Database::open(file_path).unwrap();
+        assert_eq!(db.count::<Invoice>().unwrap(), 0);
+        assert!(db.get_with_deleted::<Invoice>(&1).unwrap().unwrap().deleted);
+    });
+}
@@ -0,0 +1,138 @@
+use joydb::adapters::{Adapter, PartitionedAdapter, SqliteAdapter};
+use joydb::{Joydb, JoydbConfig, JoydbMode, Relation, SyncPolicy};
+use test_suite::database::{DatabaseState, PostTag, User};
+use test_suite::helpers::with_db_file_path;
+use uuid::Uuid;
+
+type Db = Joydb<DatabaseState, SqliteAdapter>;
+
+test_suite::adapter_scenarios!(SqliteAdapter);
+
+fn user(name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        age: 30,
+    }
+}
+
+/// Reads `(id, record)` rows of a table directly with SQLite.
+/// A missing table has no rows.
+fn read_rows(file_path: &str, table: &str) -> Vec<(String, String)> {
+    let connection = rusqlite::Connection::open(file_path).unwrap();
+    let table_exists = connection
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")
+        .unwrap()
+        .exists([table])
+        .unwrap();
+    if !table_exists {
+        return Vec::new();
+    }
+
+    let sql = format!(r#"SELECT id, record FROM "{table}" ORDER BY rowid"#);
+    let mut statement = connection.prepare(&sql).unwrap();
+    statement
+        .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+}
+
+#[test]
+fn should_store_records_as_json_keyed_by_serialized_id() {
+    with_db_file_path(|file_path| {
+        let alice = user("Alice");
+        {
+            let db = Db::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+        }
+
+        let rows = read_rows(file_path, "User");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, format!("\"{}\"", alice.id));
+        assert_eq!(rows[0].1, serde_json::to_string(&alice).unwrap());
+    });
+}
+
+#[test]
+fn should_write_only_changed_records_and_keep_order() {
+    with_db_file_path(|file_path| {
+        let alice = user("Alice");
+        let bob = user("Bob");
+        let charlie = user("Charlie");
+        {
+            let db = Db::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&bob).unwrap();
+            db.insert(&charlie).unwrap();
+
+            db.update(&User {
+                name: "Alice Updated".to_string(),
+                ..alice.clone()
+            })
+            .unwrap();
+            db.delete::<User>(&bob.id).unwrap();
+        }
+
+        let names: Vec<String> = Db::open(file_path)
+            .unwrap()
+            .get_all::<User>()
+            .unwrap()
+            .into_iter()
+            .map(|user| user.name)
+            .collect();
+        assert_eq!(names, vec!["Alice Updated", "Charlie"]);
+    });
+}
+
+#[test]
+fn should_write_all_dirty_relations_on_flush() {
+    with_db_file_path(|file_path| {
+        let db = Db::open_with_config(JoydbConfig::new(JoydbMode::Persistent {
+            adapter: SqliteAdapter::new(file_path),
+            sync_policy: SyncPolicy::Manual,
+        }))
+        .unwrap();
+        let names = ["Alice", "Bob", "Charlie", "Dave", "Eve"];
+        for name in names {
+            db.insert(&user(name)).unwrap();
+        }
+        db.insert(&PostTag {
+            post_id: Uuid::new_v4(),
+            tag_id: Uuid::new_v4(),
+        })
+        .unwrap();
+        assert!(read_rows(file_path, "User").is_empty());
+
+        db.flush().unwrap();
+        assert_eq!(read_rows(file_path, "PostTag").len(), 1);
+
+        // Records inserted within one flush keep their order
+        let stored_names: Vec<String> = read_rows(file_path, "User")
+            .into_iter()
+            .map(|(_, record)| serde_json::from_str::<User>(&record).unwrap().name)
+            .collect();
+        assert_eq!(stored_names, names);
+    });
+}
+
+#[test]
+fn should_open_connection_read_only_in_read_only_mode() {
+    with_db_file_path(|file_path| {
+        let alice = user("Alice");
+        {
+            let db = Db::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+        }
+
+        let adapter = SqliteAdapter::new(file_path);
+        adapter.load_state_read_only::<DatabaseState>().unwrap();
+
+        let relation = Relation::<User>::default();
+        assert!(adapter.write_relation(&relation).is_err());
+        assert_eq!(read_rows(file_path, "User").len(), 1);
+
+        let db = Db::open_read_only(file_path).unwrap();
+        assert_eq!(db.get::<User>(&alice.id).unwrap().unwrap().name, "Alice");
+    });
+}
@@ -0,0 +1,91 @@
+use joydb::{Change, JoydbError};
+use test_suite::database::{Database, Tag, User};
+use test_suite::helpers::with_open_db;
+use uuid::Uuid;
+
+fn new_tag(name: &str) -> Tag {
+    Tag {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+    }
+}
+
+#[test]
+fn should_receive_changes_of_subscribed_model() {
+    with_open_db(|db| {
+        let changes = db.subscribe::<Tag>();
+
+        let rust = new_tag("rust");
+        db.insert(&rust).unwrap();
+
+        let renamed = Tag {
+            name: "rustlang".to_string(),
+            ..rust.clone()
+        };
+        db.update(&renamed).unwrap();
+        db.delete::<Tag>(&rust.id).unwrap();
+
+        // Changes of other models are not received
+        db.insert(&User {
+            id: Uuid::new_v4(),
+            name: "Alice".to_string(),
+            age: 30,
+        })
+        .unwrap();
+
+        let received: Vec<Change<Tag>> = changes.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                Change::Inserted(rust.clone()),
+                Change::Updated {
+                    old: rust,
+                    new: renamed.clone()
+                },
+                Change::Deleted(renamed),
+            ]
+        );
+    });
+}
+
+#[test]
+fn should_receive_changes_of_transaction_only_on_commit() {
+    let db = Database::new_in_memory().unwrap();
+    let changes = db.subscribe::<Tag>();
+
+    let rust = new_tag("rust");
+    let result = db.transaction(|tx| {
+        tx.insert(&rust)?;
+        tx.insert(&rust)
+    });
+    assert!(matches!(result, Err(JoydbError::DuplicatedId { .. })));
+    assert!(changes.try_recv().is_err());
+
+    db.transaction(|tx| {
+        tx.insert(&rust)?;
+        tx.insert(&new_tag("rustlang"))
+    })
+    .unwrap();
+    assert_eq!(changes.try_iter().count(), 2);
+}
+
+#[test]
+fn should_unsubscribe_when_receiver_is_dropped() {
+    let db = Database::new_in_memory().unwrap();
+    let dropped = db.subscribe::<Tag>();
+    let kept = db.subscribe::<Tag>();
+
+    db.insert(&new_tag("rust")).unwrap();
+    drop(dropped);
+    db.insert(&new_tag("joydb")).unwrap();
+    assert_eq!(kept.try_iter().count(), 2);
+
+    drop(kept);
+    db.insert(&new_tag("serde")).unwrap();
+    assert_eq!(db.count::<Tag>().unwrap(), 3);
+
+    // Subscribing again works after everybody has unsubscribed
+    let changes = db.subscribe::<Tag>();
+    db.insert(&new_tag("csv")).unwrap();
+    assert_eq!(changes.try_iter().count(), 1);
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use joydb::{Clock, Joydb, JoydbConfig, JoydbMode, Model, adapters::JsonAdapter};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Model)]
+struct Note {
+    id: u32,
+    text: String,
+    #[joydb(created_at)]
+    created_at: SystemTime,
+    #[joydb(updated_at)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+joydb::state! {
+    State,
+    models: [Note],
+}
+
+type Database = Joydb<State, JsonAdapter>;
+
+/// A clock that moves forward only when it's told to.
+#[derive(Debug)]
+struct FakeClock(Mutex<SystemTime>);
+
+impl FakeClock {
+    fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> SystemTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+const START: SystemTime = SystemTime::UNIX_EPOCH;
+
+fn open_db() -> (Database, Arc<FakeClock>) {
+    let clock = Arc::new(FakeClock(Mutex::new(START)));
+    let config = JoydbConfig::new(JoydbMode::InMemory).with_clock(clock.clone());
+    let db = Database::open_with_config(config).unwrap();
+    (db, clock)
+}
+
+fn note(id: u32, text: &str) -> Note {
+    Note {
+        id,
+        text: text.to_string(),
+        created_at: SystemTime::UNIX_EPOCH,
+        updated_at: None,
+    }
+}
+
+fn at(seconds: u64) -> SystemTime {
+    START + Duration::from_secs(seconds)
+}
+
+#[test]
+fn should_fill_timestamps_on_insert() {
+    let (db, clock) = open_db();
+
+    clock.advance(Duration::from_secs(10));
+    db.insert(&note(1, "first")).unwrap();
+    clock.advance(Duration::from_secs(10));
+    db.upsert(&note(2, "second")).unwrap();
+
+    let first = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(first.created_at, at(10));
+    assert_eq!(first.updated_at, Some(at(10).into()));
+
+    let second = db.get::<Note>(&2).unwrap().unwrap();
+    assert_eq!(second.created_at, at(20));
+    assert_eq!(second.updated_at, Some(at(20).into()));
+}
+
+#[test]
+fn should_bump_updated_at_on_update() {
+    let (db, clock) = open_db();
+    db.insert(&note(1, "draft")).unwrap();
+
+    clock.advance(Duration::from_secs(10));
+    let mut updated = db.get::<Note>(&1).unwrap().unwrap();
+    updated.text = "final".to_string();
+    db.update(&updated).unwrap();
+
+    let stored = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(stored.created_at, START);
+    assert_eq!(stored.updated_at, Some(at(10).into()));
+
+    clock.advance(Duration::from_secs(10));
+    db.upsert(&stored).unwrap();
+    let stored = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(stored.updated_at, Some(at(20).into()));
+
+    clock.advance(Duration::from_secs(10));
+    db.update_with::<Note, _>(&1, |note| note.text = "edited".to_string())
+        .unwrap();
+    let stored = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(stored.updated_at, Some(at(30).into()));
+}
+
+#[test]
+fn should_keep_created_at_when_updated_with_fresh_record() {
+    let (db, clock) = open_db();
+    clock.advance(Duration::from_secs(10));
+    db.insert(&note(1, "draft")).unwrap();
+
+    clock.advance(Duration::from_secs(10));
+    db.update(&note(1, "final")).unwrap();
+    let stored = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(stored.text, "final");
+    assert_eq!(stored.created_at, at(10));
+    assert_eq!(stored.updated_at, Some(at(20).into()));
+
+    clock.advance(Duration::from_secs(10));
+    db.upsert(&note(1, "again")).unwrap();
+    let stored = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(stored.created_at, at(10));
+    assert_eq!(stored.updated_at, Some(at(30).into()));
+}
+
+#[test]
+fn should_not_bump_updated_at_if_nothing_changed() {
+    let (db, clock) = open_db();
+    db.insert(&note(1, "draft")).unwrap();
+
+    clock.advance(Duration::from_secs(10));
+    db.update_with::<Note, _>(&1, |note| note.text = "draft".to_string())
+        .unwrap();
+
+    let stored = db.get::<Note>(&1).unwrap().unwrap();
+    assert_eq!(stored.updated_at, Some(START.into()));
+}
@@ -0,0 +1,116 @@
+use joydb::adapters::{TomlAdapter, TomlPartitionedAdapter};
+use joydb::{Joydb, JoydbError, Model, state};
+use serde::{Deserialize, Serialize};
+use test_suite::database::{DatabaseState, Post, User};
+use test_suite::helpers::{with_db_dir_path, with_db_file_path};
+use uuid::Uuid;
+
+mod unified {
+    test_suite::adapter_scenarios!(super::TomlAdapter);
+}
+
+mod partitioned {
+    test_suite::adapter_scenarios!(super::TomlPartitionedAdapter);
+}
+
+fn alice() -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: "Alice".to_string(),
+        age: 30,
+    }
+}
+
+fn post_of(user: &User) -> Post {
+    Post {
+        id: Uuid::new_v4(),
+        content: "Hello, TOML!".to_string(),
+        user_id: user.id,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Model)]
+struct Survey {
+    id: u32,
+    answers: Vec<Option<String>>,
+}
+
+state! {
+    SurveyState,
+    models: [Survey],
+}
+
+fn survey_with_missing_answer() -> Survey {
+    Survey {
+        id: 1,
+        answers: vec![Some("yes".to_string()), None],
+    }
+}
+
+#[test]
+fn should_persist_state_in_single_toml_file() {
+    with_db_file_path(|file_path| {
+        let alice = alice();
+        let post = post_of(&alice);
+        {
+            let db = Joydb::<DatabaseState, TomlAdapter>::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&post).unwrap();
+        }
+
+        let content = std::fs::read_to_string(file_path).unwrap();
+        assert!(content.contains("[[User]]"));
+        assert!(content.contains("[[Post]]"));
+
+        let db = Joydb::<DatabaseState, TomlAdapter>::open(file_path).unwrap();
+        assert_eq!(db.get::<User>(&alice.id).unwrap().unwrap().name, "Alice");
+        assert_eq!(
+            db.get::<Post>(&post.id).unwrap().unwrap().content,
+            post.content
+        );
+    });
+}
+
+#[test]
+fn should_persist_every_relation_in_separate_toml_file() {
+    with_db_dir_path(|dir_path| {
+        let alice = alice();
+        let post = post_of(&alice);
+        {
+            let db = Joydb::<DatabaseState, TomlPartitionedAdapter>::open(dir_path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&post).unwrap();
+        }
+
+        let users = std::fs::read_to_string(format!("{dir_path}/User.toml")).unwrap();
+        assert!(users.contains("[[User]]"));
+        assert!(users.contains("name = \"Alice\""));
+        assert!(std::fs::exists(format!("{dir_path}/Post.toml")).unwrap());
+
+        let db = Joydb::<DatabaseState, TomlPartitionedAdapter>::open(dir_path).unwrap();
+        assert_eq!(db.get::<User>(&alice.id).unwrap().unwrap().age, 30);
+        assert_eq!(db.count::<Post>().unwrap(), 1);
+    });
+}
+
+#[test]
+fn should_name_model_when_value_cannot_be_represented_in_toml() {
+    with_db_file_path(|file_path| {
+        let db = Joydb::<SurveyState, TomlAdapter>::open(file_path).unwrap();
+        let err = db.insert(&survey_with_missing_answer()).unwrap_err();
+
+        assert!(matches!(err, JoydbError::Serialize(_)));
+        assert!(err.to_string().contains("Survey"), "{err}");
+    });
+}
+
+#[test]
+fn should_name_model_when_value_cannot_be_represented_in_partitioned_toml() {
+    with_db_dir_path(|dir_path| {
+        let db = Joydb::<SurveyState, TomlPartitionedAdapter>::open(dir_path).unwrap();
+        let err = db.insert(&survey_with_missing_answer()).unwrap_err();
+
+        assert!(matches!(err, JoydbError::Serialize(_)));
+        assert!(err.to_string().contains("Survey"), "{err}");
+    });
+}
@@ -0,0 +1,114 @@
+use joydb::JoydbError;
+use test_suite::helpers::with_open_db;
+use uuid::Uuid;
+
+use test_suite::database::{Comment, Post, User};
+
+fn alice() -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: "Alice".to_string(),
+        age: 30,
+    }
+}
+
+#[test]
+fn should_commit_all_changes_when_closure_returns_ok() {
+    with_open_db(|db| {
+        let alice = alice();
+        let post = Post {
+            id: Uuid::new_v4(),
+            content: "Hello".to_string(),
+            user_id: alice.id,
+        };
+
+        let post_id = db
+            .transaction(|tx| {
+                tx.insert(&alice)?;
+                tx.insert(&post)?;
+                Ok::<_, JoydbError>(post.id)
+            })
+            .unwrap();
+
+        assert_eq!(db.count::<User>().unwrap(), 1);
+        let post = db.get::<Post>(&post_id).unwrap().unwrap();
+        assert_eq!(post.user_id, alice.id);
+    });
+}
+
+#[test]
+fn should_rollback_all_changes_when_closure_returns_err() {
+    with_open_db(|db| {
+        let alice = alice();
+        db.insert(&alice).unwrap();
+
+        let err = db
+            .transaction(|tx| {
+                let mut alice = tx.get::<User>(&alice.id)?.unwrap();
+                alice.age = 31;
+                tx.update(&alice)?;
+
+                let bob = User {
+                    id: Uuid::new_v4(),
+                    name: "Bob".to_string(),
+                    age: 25,
+                };
+                tx.insert(&bob)?;
+                assert_eq!(tx.count::<User>()?, 2);
+
+                // Fails, because Alice already exists
+                tx.insert(&alice)?;
+                Ok::<_, JoydbError>(())
+            })
+            .unwrap_err();
+
+        assert!(matches!(err, JoydbError::DuplicatedId { .. }));
+
+        assert_eq!(db.count::<User>().unwrap(), 1);
+        let same_alice = db.get::<User>(&alice.id).unwrap().unwrap();
+        assert_eq!(same_alice.age, 30);
+    });
+}
+
+#[test]
+fn should_rollback_deletions() {
+    with_open_db(|db| {
+        let alice = alice();
+        db.insert(&alice).unwrap();
+
+        let result: Result<(), JoydbError> = db.transaction(|tx| {
+            tx.delete::<User>(&alice.id)?;
+            assert_eq!(tx.count::<User>()?, 0);
+            Err(JoydbError::NotFound {
+                id: "1".to_string(),
+                model: "Post".to_string(),
+            })
+        });
+        assert!(result.is_err());
+
+        let same_alice = db.get::<User>(&alice.id).unwrap().unwrap();
+        assert_eq!(same_alice.name, "Alice");
+    });
+}
+
+#[test]
+fn should_rollback_generated_ids() {
+    with_open_db(|db| {
+        let comment = Comment {
+            id: 0,
+            post_id: Uuid::new_v4(),
+            text: "Hello".to_string(),
+        };
+
+        let result: Result<(), JoydbError> = db.transaction(|tx| {
+            assert_eq!(tx.insert_new(&comment)?, 1);
+            Err(JoydbError::NotFound {
+                id: "1".to_string(),
+                model: "Comment".to_string(),
+            })
+        });
+        assert!(result.is_err());
+
+        assert_eq!(db.insert_new(&comment).unwrap(), 1);
+    });
+}
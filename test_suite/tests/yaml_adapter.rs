@@ -0,0 +1,73 @@
+use joydb::Joydb;
+use joydb::adapters::{YamlAdapter, YamlPartitionedAdapter};
+use test_suite::database::{DatabaseState, Post, User};
+use test_suite::helpers::{with_db_dir_path, with_db_file_path};
+use uuid::Uuid;
+
+mod unified {
+    test_suite::adapter_scenarios!(super::YamlAdapter);
+}
+
+mod partitioned {
+    test_suite::adapter_scenarios!(super::YamlPartitionedAdapter);
+}
+
+fn alice() -> User {
+    User {
+        id: Uuid::new_v4(),
+        name: "Alice".to_string(),
+        age: 30,
+    }
+}
+
+fn post_of(user: &User) -> Post {
+    Post {
+        id: Uuid::new_v4(),
+        content: "Hello, YAML!".to_string(),
+        user_id: user.id,
+    }
+}
+
+#[test]
+fn should_persist_state_in_single_yaml_file() {
+    with_db_file_path(|file_path| {
+        let alice = alice();
+        let post = post_of(&alice);
+        {
+            let db = Joydb::<DatabaseState, YamlAdapter>::open(file_path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&post).unwrap();
+        }
+
+        let content = std::fs::read_to_string(file_path).unwrap();
+        assert!(content.contains("name: Alice"));
+
+        let db = Joydb::<DatabaseState, YamlAdapter>::open(file_path).unwrap();
+        assert_eq!(db.get::<User>(&alice.id).unwrap().unwrap().name, "Alice");
+        assert_eq!(
+            db.get::<Post>(&post.id).unwrap().unwrap().content,
+            post.content
+        );
+    });
+}
+
+#[test]
+fn should_persist_every_relation_in_separate_yaml_file() {
+    with_db_dir_path(|dir_path| {
+        let alice = alice();
+        let post = post_of(&alice);
+        {
+            let db = Joydb::<DatabaseState, YamlPartitionedAdapter>::open(dir_path).unwrap();
+            db.insert(&alice).unwrap();
+            db.insert(&post).unwrap();
+        }
+
+        let users = std::fs::read_to_string(format!("{dir_path}/User.yaml")).unwrap();
+        assert!(users.contains("name: Alice"));
+        assert!(std::fs::exists(format!("{dir_path}/Post.yaml")).unwrap());
+
+        let db = Joydb::<DatabaseState, YamlPartitionedAdapter>::open(dir_path).unwrap();
+        assert_eq!(db.get::<User>(&alice.id).unwrap().unwrap().age, 30);
+        assert_eq!(db.count::<Post>().unwrap(), 1);
+    });
+}